@@ -0,0 +1,487 @@
+//! `staking-rewards`: routine pool administration and staking from the
+//! command line, built on `staking-rewards-sdk`.
+
+mod output;
+mod rpc;
+
+use std::path::PathBuf;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anyhow::{anyhow, Context, Result};
+use clap::{Args, Parser, Subcommand};
+use serde_json::Value;
+use solana_keypair::{read_keypair_file, Keypair};
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+use staking_rewards_sdk::instructions::{self, StreamClaim};
+use staking_rewards_sdk::{decode_pool, decode_user_stake, estimate_pending_rewards, Pool, PoolKeys};
+
+use crate::rpc::RpcClient;
+
+#[derive(Parser)]
+#[command(name = "staking-rewards", version, about)]
+struct Cli {
+    /// JSON-RPC endpoint of the cluster
+    #[arg(
+        long,
+        short = 'u',
+        global = true,
+        env = "STAKING_RPC_URL",
+        default_value = "http://127.0.0.1:8899"
+    )]
+    url: String,
+
+    /// Keypair file that signs and pays [default: ~/.config/solana/id.json]
+    #[arg(long, short = 'k', global = true, env = "STAKING_KEYPAIR")]
+    keypair: Option<PathBuf>,
+
+    /// Print results as JSON
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// The pool is addressed by its two mints, like its PDA.
+#[derive(Args)]
+struct PoolArgs {
+    #[arg(long)]
+    stake_mint: Pubkey,
+
+    #[arg(long)]
+    reward_mint: Pubkey,
+}
+
+impl PoolArgs {
+    fn keys(&self) -> PoolKeys {
+        PoolKeys::new(self.stake_mint, self.reward_mint)
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a pool; the keypair becomes its admin
+    InitPool {
+        #[command(flatten)]
+        pool: PoolArgs,
+
+        /// Reward tokens emitted per second across all stakers
+        #[arg(long)]
+        rate: u64,
+    },
+
+    /// Fund the reward vault, optionally starting a reward period
+    DepositRewards {
+        #[command(flatten)]
+        pool: PoolArgs,
+
+        #[arg(long)]
+        amount: u64,
+
+        /// Length of the new reward period in seconds; 0 only funds the vault
+        #[arg(long, default_value_t = 0)]
+        duration: u64,
+    },
+
+    /// Return reward tokens no staker is owed to the keypair
+    WithdrawExcessRewards {
+        #[command(flatten)]
+        pool: PoolArgs,
+
+        #[arg(long)]
+        amount: u64,
+    },
+
+    /// Sweep every vault to the keypair and close the pool
+    ClosePool {
+        #[command(flatten)]
+        pool: PoolArgs,
+    },
+
+    /// Change the primary reward rate
+    SetRate {
+        #[command(flatten)]
+        pool: PoolArgs,
+
+        #[arg(long)]
+        rate: u64,
+    },
+
+    /// Stake tokens from the keypair's associated token account
+    Stake {
+        #[command(flatten)]
+        pool: PoolArgs,
+
+        #[arg(long)]
+        amount: u64,
+    },
+
+    /// Stake tokens from the keypair's associated token account into another
+    /// wallet's position
+    StakeFor {
+        #[command(flatten)]
+        pool: PoolArgs,
+
+        /// Wallet that will own the position
+        #[arg(long)]
+        beneficiary: Pubkey,
+
+        #[arg(long)]
+        amount: u64,
+    },
+
+    /// Unstake tokens back to the keypair's associated token account
+    Unstake {
+        #[command(flatten)]
+        pool: PoolArgs,
+
+        #[arg(long)]
+        amount: u64,
+
+        /// Close the position and refund its rent if it is left empty
+        #[arg(long)]
+        close: bool,
+    },
+
+    /// Claim the primary reward, or with --all every reward stream too
+    Claim {
+        #[command(flatten)]
+        pool: PoolArgs,
+
+        #[arg(long)]
+        all: bool,
+
+        /// Close the position and refund its rent if it is left empty
+        #[arg(long, conflicts_with = "all")]
+        close: bool,
+    },
+
+    /// Unstake everything and claim every reward in one transaction
+    Exit {
+        #[command(flatten)]
+        pool: PoolArgs,
+
+        /// Close the position and refund its rent if it is left empty
+        #[arg(long)]
+        close: bool,
+    },
+
+    /// Close the keypair's empty position and refund its rent
+    ClosePosition {
+        #[command(flatten)]
+        pool: PoolArgs,
+    },
+
+    /// Show a pool account
+    Pool {
+        #[command(flatten)]
+        pool: PoolArgs,
+    },
+
+    /// Show a position and its pending rewards as of the cluster clock
+    User {
+        #[command(flatten)]
+        pool: PoolArgs,
+
+        /// Position owner [default: the keypair's address]
+        #[arg(long)]
+        owner: Option<Pubkey>,
+    },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let client = RpcClient::new(&cli.url);
+
+    let value = match &cli.command {
+        Command::InitPool { pool, rate } => {
+            let payer = load_keypair(&cli)?;
+            let ix = instructions::initialize_pool(
+                &pool.keys(),
+                &payer.pubkey(),
+                &token_program(&client, &pool.stake_mint)?,
+                &token_program(&client, &pool.reward_mint)?,
+                *rate,
+            );
+            send(&client, &payer, vec![ix])?
+        }
+        Command::DepositRewards {
+            pool,
+            amount,
+            duration,
+        } => {
+            let payer = load_keypair(&cli)?;
+            let token_program = token_program(&client, &pool.reward_mint)?;
+            let ix = instructions::deposit_rewards(
+                &pool.keys(),
+                &payer.pubkey(),
+                &associated_account(&payer.pubkey(), &pool.reward_mint, &token_program),
+                &token_program,
+                *amount,
+                *duration,
+            );
+            send(&client, &payer, vec![ix])?
+        }
+        Command::WithdrawExcessRewards { pool, amount } => {
+            let payer = load_keypair(&cli)?;
+            let token_program = token_program(&client, &pool.reward_mint)?;
+            let ix = instructions::withdraw_excess_rewards(
+                &pool.keys(),
+                &payer.pubkey(),
+                &associated_account(&payer.pubkey(), &pool.reward_mint, &token_program),
+                &token_program,
+                *amount,
+            );
+            send(&client, &payer, vec![ix])?
+        }
+        Command::ClosePool { pool } => {
+            let payer = load_keypair(&cli)?;
+            let keys = pool.keys();
+            let admin = payer.pubkey();
+            let stake_program = token_program(&client, &pool.stake_mint)?;
+            let reward_program = token_program(&client, &pool.reward_mint)?;
+
+            let state = decode_pool(&client.require_account(&keys.pool)?.data)?;
+            let ix = instructions::close_pool(
+                &keys,
+                &admin,
+                &associated_account(&admin, &pool.stake_mint, &stake_program),
+                &associated_account(&admin, &pool.reward_mint, &reward_program),
+                &stake_program,
+                &reward_program,
+                &stream_accounts(&client, &state, &admin)?,
+            );
+            send(&client, &payer, vec![ix])?
+        }
+        Command::SetRate { pool, rate } => {
+            let payer = load_keypair(&cli)?;
+            let ix = instructions::set_reward_rate(&pool.keys(), &payer.pubkey(), *rate);
+            send(&client, &payer, vec![ix])?
+        }
+        Command::Stake { pool, amount } => {
+            let payer = load_keypair(&cli)?;
+            let token_program = token_program(&client, &pool.stake_mint)?;
+            let ix = instructions::stake(
+                &pool.keys(),
+                &payer.pubkey(),
+                &associated_account(&payer.pubkey(), &pool.stake_mint, &token_program),
+                &token_program,
+                *amount,
+            );
+            send(&client, &payer, vec![ix])?
+        }
+        Command::StakeFor {
+            pool,
+            beneficiary,
+            amount,
+        } => {
+            let payer = load_keypair(&cli)?;
+            let token_program = token_program(&client, &pool.stake_mint)?;
+            let ix = instructions::stake_for(
+                &pool.keys(),
+                &payer.pubkey(),
+                &associated_account(&payer.pubkey(), &pool.stake_mint, &token_program),
+                &token_program,
+                beneficiary,
+                *amount,
+            );
+            send(&client, &payer, vec![ix])?
+        }
+        Command::Unstake {
+            pool,
+            amount,
+            close,
+        } => {
+            let payer = load_keypair(&cli)?;
+            let token_program = token_program(&client, &pool.stake_mint)?;
+            let ix = instructions::unstake(
+                &pool.keys(),
+                &payer.pubkey(),
+                &associated_account(&payer.pubkey(), &pool.stake_mint, &token_program),
+                &token_program,
+                *amount,
+                *close,
+            );
+            send(&client, &payer, vec![ix])?
+        }
+        Command::Claim { pool, all, close } => {
+            let payer = load_keypair(&cli)?;
+            let keys = pool.keys();
+            let user = payer.pubkey();
+            let reward_program = token_program(&client, &pool.reward_mint)?;
+            let user_reward_ata = associated_account(&user, &pool.reward_mint, &reward_program);
+
+            let ix = if *all {
+                let state = decode_pool(&client.require_account(&keys.pool)?.data)?;
+                let streams = stream_accounts(&client, &state, &user)?;
+
+                instructions::claim_all_rewards(
+                    &keys,
+                    &user,
+                    &user_reward_ata,
+                    &reward_program,
+                    &streams,
+                )
+            } else {
+                instructions::claim_rewards(
+                    &keys,
+                    &user,
+                    &user_reward_ata,
+                    &reward_program,
+                    *close,
+                )
+            };
+            send(&client, &payer, vec![ix])?
+        }
+        Command::Exit { pool, close } => {
+            let payer = load_keypair(&cli)?;
+            let user = payer.pubkey();
+            let stake_program = token_program(&client, &pool.stake_mint)?;
+            let reward_program = token_program(&client, &pool.reward_mint)?;
+            let keys = pool.keys();
+            let state = decode_pool(&client.require_account(&keys.pool)?.data)?;
+            let streams = stream_accounts(&client, &state, &user)?;
+            let ix = instructions::exit(
+                &keys,
+                &user,
+                &associated_account(&user, &pool.stake_mint, &stake_program),
+                &associated_account(&user, &pool.reward_mint, &reward_program),
+                &stake_program,
+                &reward_program,
+                &streams,
+                *close,
+            );
+            send(&client, &payer, vec![ix])?
+        }
+        Command::ClosePosition { pool } => {
+            let payer = load_keypair(&cli)?;
+            let ix = instructions::close_user_stake(&pool.keys(), &payer.pubkey());
+            send(&client, &payer, vec![ix])?
+        }
+        Command::Pool { pool } => {
+            let keys = pool.keys();
+            let state = decode_pool(&client.require_account(&keys.pool)?.data)?;
+            output::pool(&keys.pool, &state)
+        }
+        Command::User { pool, owner } => {
+            let owner = match owner {
+                Some(owner) => *owner,
+                None => load_keypair(&cli)?.pubkey(),
+            };
+            let keys = pool.keys();
+            let address = keys.user_stake(&owner);
+
+            let state = decode_pool(&client.require_account(&keys.pool)?.data)?;
+            let position = decode_user_stake(&client.require_account(&address)?.data)?;
+            let now = cluster_time(&client)?;
+            let pending = estimate_pending_rewards(&state, &position, now)?;
+
+            output::user(&address, &position, &pending, now)
+        }
+    };
+
+    output::print(&value, cli.json);
+    Ok(())
+}
+
+fn load_keypair(cli: &Cli) -> Result<Keypair> {
+    let path = match &cli.keypair {
+        Some(path) => path.clone(),
+        None => {
+            let home = std::env::var_os("HOME").ok_or_else(|| anyhow!("HOME is not set"))?;
+            PathBuf::from(home).join(".config/solana/id.json")
+        }
+    };
+
+    read_keypair_file(&path).map_err(|err| anyhow!("reading keypair {}: {err}", path.display()))
+}
+
+/// The token program that owns `mint`, i.e. SPL Token or Token-2022.
+fn token_program(client: &RpcClient, mint: &Pubkey) -> Result<Pubkey> {
+    let account = client
+        .require_account(mint)
+        .with_context(|| format!("looking up mint {mint}"))?;
+
+    Ok(account.owner)
+}
+
+fn associated_account(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, token_program)
+}
+
+/// `owner`'s associated token account for every active reward stream of
+/// `pool`, in pool order.
+fn stream_accounts(client: &RpcClient, pool: &Pool, owner: &Pubkey) -> Result<Vec<StreamClaim>> {
+    pool.active_reward_streams()
+        .iter()
+        .map(|stream| {
+            let stream_program = token_program(client, &stream.mint)?;
+            Ok(StreamClaim {
+                mint: stream.mint,
+                user_reward_ata: associated_account(owner, &stream.mint, &stream_program),
+                token_program: stream_program,
+            })
+        })
+        .collect()
+}
+
+/// Unix time of the cluster's `Clock` sysvar, so estimates match what the
+/// program would compute rather than the local wall clock.
+fn cluster_time(client: &RpcClient) -> Result<i64> {
+    // slot, epoch_start_timestamp, epoch, leader_schedule_epoch, unix_timestamp
+    const UNIX_TIMESTAMP_OFFSET: usize = 32;
+
+    let data = client.require_account(&sysvar::clock::ID)?.data;
+    let bytes = data
+        .get(UNIX_TIMESTAMP_OFFSET..UNIX_TIMESTAMP_OFFSET + 8)
+        .ok_or_else(|| anyhow!("clock sysvar is too short"))?;
+
+    Ok(i64::from_le_bytes(bytes.try_into()?))
+}
+
+fn send(client: &RpcClient, payer: &Keypair, instructions: Vec<Instruction>) -> Result<Value> {
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer.pubkey()),
+        &[payer],
+        client.latest_blockhash()?,
+    );
+
+    Ok(output::signature(&client.send_and_confirm(&transaction)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn cli_definition_is_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn parses_pool_arguments() {
+        let mint = Pubkey::new_unique();
+        let cli = Cli::try_parse_from([
+            "staking-rewards",
+            "stake",
+            "--stake-mint",
+            &mint.to_string(),
+            "--reward-mint",
+            &mint.to_string(),
+            "--amount",
+            "5",
+            "--json",
+        ])
+        .unwrap();
+
+        assert!(cli.json);
+        assert!(matches!(cli.command, Command::Stake { amount: 5, .. }));
+    }
+}
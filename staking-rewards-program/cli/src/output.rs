@@ -0,0 +1,150 @@
+//! Renders command results either as JSON or as indented `key: value` text.
+//! Both forms come from the same `serde_json::Value`, so they never disagree;
+//! `preserve_order` keeps fields in the order they are listed here.
+//! `u128` amounts are emitted as strings to survive JSON parsers that read
+//! numbers as doubles.
+
+use anchor_lang::prelude::Pubkey;
+use serde_json::{json, Value};
+use staking_rewards_sdk::{PendingRewards, Pool, UserStake};
+
+pub fn print(value: &Value, as_json: bool) {
+    if as_json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(value).unwrap_or_default()
+        );
+    } else {
+        print_text(value, 0);
+    }
+}
+
+fn print_text(value: &Value, indent: usize) {
+    let pad = "  ".repeat(indent);
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields {
+                if is_nested(field) {
+                    println!("{pad}{key}:");
+                    print_text(field, indent + 1);
+                } else {
+                    println!("{pad}{key}: {}", scalar(field));
+                }
+            }
+        }
+        Value::Array(items) if items.is_empty() => println!("{pad}(none)"),
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                println!("{pad}[{index}]");
+                print_text(item, indent + 1);
+            }
+        }
+        other => println!("{pad}{}", scalar(other)),
+    }
+}
+
+fn is_nested(value: &Value) -> bool {
+    matches!(value, Value::Object(_) | Value::Array(_))
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+pub fn signature(signature: &str) -> Value {
+    json!({ "signature": signature })
+}
+
+pub fn pool(address: &Pubkey, pool: &Pool) -> Value {
+    let streams: Vec<Value> = pool
+        .active_reward_streams()
+        .iter()
+        .map(|stream| {
+            json!({
+                "mint": stream.mint.to_string(),
+                "reward_rate_per_second": stream.reward_rate_per_second,
+                "reward_per_token_stored": stream.reward_per_token_stored.to_string(),
+                "last_update_time": stream.last_update_time,
+                "period_finish": stream.period_finish,
+                "rewards_owed": stream.rewards_owed.to_string(),
+            })
+        })
+        .collect();
+
+    let tiers: Vec<Value> = pool
+        .active_lockup_tiers()
+        .iter()
+        .map(|tier| {
+            json!({
+                "duration": tier.duration,
+                "multiplier_bps": tier.multiplier_bps,
+            })
+        })
+        .collect();
+
+    json!({
+        "address": address.to_string(),
+        "admin": pool.admin.to_string(),
+        "pending_admin": pool.pending_admin.to_string(),
+        "stake_mint": pool.stake_mint.to_string(),
+        "reward_mint": pool.reward_mint.to_string(),
+        "reward_rate_per_second": pool.reward_rate_per_second,
+        "total_staked": pool.total_staked.to_string(),
+        "reward_per_token_stored": pool.reward_per_token_stored.to_string(),
+        "last_update_time": pool.last_update_time,
+        "period_finish": pool.period_finish,
+        "reward_streams": streams,
+        "lockup_tiers": tiers,
+        "cooldown_seconds": pool.cooldown_seconds,
+        "total_unbonding": pool.total_unbonding.to_string(),
+        "paused": pool.paused,
+        "rewards_unpaid": pool.rewards_unpaid.to_string(),
+        "rewards_owed": pool.rewards_owed.to_string(),
+    })
+}
+
+pub fn user(address: &Pubkey, user: &UserStake, pending: &PendingRewards, now: i64) -> Value {
+    let unbonding: Vec<Value> = user
+        .unbonding
+        .iter()
+        .filter(|entry| entry.amount > 0)
+        .map(|entry| {
+            json!({
+                "amount": entry.amount,
+                "release_time": entry.release_time,
+            })
+        })
+        .collect();
+
+    let streams: Vec<Value> = pending
+        .streams
+        .iter()
+        .map(|stream| {
+            json!({
+                "mint": stream.mint.to_string(),
+                "pending": stream.pending.to_string(),
+            })
+        })
+        .collect();
+
+    json!({
+        "address": address.to_string(),
+        "owner": user.owner.to_string(),
+        "amount_staked": user.amount_staked.to_string(),
+        "effective_stake": user.effective_stake.to_string(),
+        "locked_amount": user.locked_amount.to_string(),
+        "lock_end": user.lock_end,
+        "lock_multiplier_bps": user.lock_multiplier_bps,
+        "last_update": user.last_update,
+        "unbonding": unbonding,
+        "pending_rewards": {
+            "as_of": now,
+            "reward_mint": pending.reward_mint.to_string(),
+            "pending": pending.pending.to_string(),
+            "streams": streams,
+        },
+    })
+}
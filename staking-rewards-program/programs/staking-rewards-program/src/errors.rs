@@ -12,4 +12,46 @@ pub enum StakingError {
     NoRewardsAccrued,
     #[msg("Overflow")]
     Overflow,
+    #[msg("Reward rate rounds to zero")]
+    RewardRateTooLow,
+    #[msg("Reward vault is empty")]
+    RewardVaultDepleted,
+    #[msg("Reward stream limit reached")]
+    RewardStreamLimitReached,
+    #[msg("Reward stream not found")]
+    RewardStreamNotFound,
+    #[msg("Reward mint already emitted by this pool")]
+    DuplicateRewardStream,
+    #[msg("Invalid reward stream accounts")]
+    InvalidRewardStreamAccounts,
+    #[msg("Stake is locked")]
+    StakeLocked,
+    #[msg("Invalid lockup tier")]
+    InvalidLockupTier,
+    #[msg("Lock would end before the current lock")]
+    LockupTooShort,
+    #[msg("No expired lock to release")]
+    NoExpiredLock,
+    #[msg("Pool requires unstaking through the cooldown queue")]
+    CooldownRequired,
+    #[msg("Too many pending unbonding entries")]
+    UnbondingQueueFull,
+    #[msg("No unbonded tokens ready to withdraw")]
+    NothingToWithdraw,
+    #[msg("Paused")]
+    Paused,
+    #[msg("Invalid pause flags")]
+    InvalidPauseFlags,
+    #[msg("Stake and reward mints differ")]
+    MintMismatch,
+    #[msg("Rewards are committed to stakers")]
+    RewardsCommitted,
+    #[msg("Pool still holds stake")]
+    PoolNotEmpty,
+    #[msg("Rewards are still owed to stakers")]
+    RewardsOutstanding,
+    #[msg("Position still holds stake or rewards")]
+    PositionNotEmpty,
+    #[msg("Invalid beneficiary")]
+    InvalidBeneficiary,
 }
\ No newline at end of file
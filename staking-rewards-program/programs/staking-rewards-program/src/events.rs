@@ -0,0 +1,215 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{LockupTier, MAX_REWARD_STREAMS};
+
+#[event]
+pub struct RewardRateUpdated {
+    pub pool: Pubkey,
+    pub old_rate: u64,
+    pub new_rate: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardsPartiallyClaimed {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub reward_mint: Pubkey,
+    pub paid: u64,
+    pub remaining: u128,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardStreamAdded {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub reward_rate_per_second: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LockupTiersUpdated {
+    pub pool: Pubkey,
+    pub tiers: Vec<LockupTier>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CooldownUpdated {
+    pub pool: Pubkey,
+    pub old_cooldown_seconds: u64,
+    pub new_cooldown_seconds: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyUnstaked {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    /// Primary rewards given up, settled or not.
+    pub forfeited_rewards: u128,
+    /// Stream rewards given up, indexed like `Pool::reward_streams`.
+    pub forfeited_stream_rewards: [u128; MAX_REWARD_STREAMS],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PauseUpdated {
+    pub pool: Pubkey,
+    pub paused: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminProposed {
+    pub pool: Pubkey,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminTransferred {
+    pub pool: Pubkey,
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolInitialized {
+    pub pool: Pubkey,
+    pub admin: Pubkey,
+    pub stake_mint: Pubkey,
+    pub reward_mint: Pubkey,
+    pub reward_rate_per_second: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted for the primary reward and for reward streams; `reward_mint`
+/// tells them apart. `amount` is what the vault received after any fee.
+#[event]
+pub struct RewardsDeposited {
+    pub pool: Pubkey,
+    pub reward_mint: Pubkey,
+    pub amount: u64,
+    pub vault_balance: u64,
+    pub reward_rate_per_second: u64,
+    pub period_finish: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Staked {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    /// Who transferred the tokens; differs from `user` only for `stake_for`.
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub amount_staked: u128,
+    pub total_staked: u128,
+    pub timestamp: i64,
+}
+
+/// `received` is what reached the user after any transfer fee.
+#[event]
+pub struct Unstaked {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub received: u64,
+    pub amount_staked: u128,
+    pub total_staked: u128,
+    pub timestamp: i64,
+}
+
+/// `remaining` is what the user is still owed in `reward_mint` afterwards.
+#[event]
+pub struct RewardsClaimed {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub reward_mint: Pubkey,
+    pub amount: u64,
+    pub received: u64,
+    pub remaining: u128,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardsCompounded {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub amount_staked: u128,
+    pub total_staked: u128,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakeLocked {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub locked_amount: u128,
+    pub lock_end: i64,
+    pub multiplier_bps: u16,
+    pub effective_stake: u128,
+    pub total_staked: u128,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LockExpired {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub effective_stake: u128,
+    pub total_staked: u128,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UnstakeRequested {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub release_time: i64,
+    pub amount_staked: u128,
+    pub total_staked: u128,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UnbondedWithdrawn {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub received: u64,
+    pub total_unbonding: u128,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ExcessRewardsWithdrawn {
+    pub pool: Pubkey,
+    pub admin: Pubkey,
+    pub amount: u64,
+    pub vault_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolClosed {
+    pub pool: Pubkey,
+    pub admin: Pubkey,
+    pub stake_swept: u64,
+    pub rewards_swept: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UserStakeClosed {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub timestamp: i64,
+}
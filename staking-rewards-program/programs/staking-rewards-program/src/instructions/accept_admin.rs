@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Pool;
+use crate::errors::StakingError;
+use crate::events::AdminTransferred;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub new_admin: Signer<'info>,
+
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+}
+
+pub fn handler(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    require!(pool.pending_admin != Pubkey::default(), StakingError::Unauthorized);
+    require_keys_eq!(
+        pool.pending_admin,
+        ctx.accounts.new_admin.key(),
+        StakingError::Unauthorized
+    );
+
+    let old_admin = pool.admin;
+    pool.admin = pool.pending_admin;
+    pool.pending_admin = Pubkey::default();
+
+    emit_cpi!(AdminTransferred {
+        pool: pool.key(),
+        old_admin,
+        new_admin: pool.admin,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::{Pool, RewardStream, MAX_REWARD_STREAMS};
+use crate::errors::StakingError;
+use crate::events::RewardStreamAdded;
+use crate::utils::update_pool;
+use crate::STREAM_VAULT_SEED;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AddRewardStream<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [STREAM_VAULT_SEED, pool.key().as_ref(), reward_mint.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = pool,
+        token::token_program = token_program,
+    )]
+    pub stream_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<AddRewardStream>, reward_rate_per_second: u64) -> Result<()> {
+    require_keys_eq!(
+        ctx.accounts.pool.admin,
+        ctx.accounts.admin.key(),
+        StakingError::Unauthorized
+    );
+
+    let pool = &mut ctx.accounts.pool;
+    let mint = ctx.accounts.reward_mint.key();
+
+    require!(
+        (pool.reward_stream_count as usize) < MAX_REWARD_STREAMS,
+        StakingError::RewardStreamLimitReached
+    );
+    require!(
+        mint != pool.reward_mint && pool.find_reward_stream(&mint).is_none(),
+        StakingError::DuplicateRewardStream
+    );
+
+    // Checkpoint existing streams so the new one only accrues from now on
+    let now = Clock::get()?.unix_timestamp;
+    update_pool(pool, now)?;
+
+    let index = pool.reward_stream_count as usize;
+
+    pool.reward_streams[index] = RewardStream {
+        mint,
+        reward_rate_per_second,
+        reward_per_token_stored: 0,
+        last_update_time: now,
+        period_finish: 0,
+        rewards_owed: 0,
+    };
+    pool.reward_stream_count += 1;
+
+    emit_cpi!(RewardStreamAdded {
+        pool: pool.key(),
+        mint,
+        reward_rate_per_second,
+        timestamp: now,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::StakingError;
+use crate::state::PAUSE_CLAIM;
+use crate::events::{RewardsClaimed, RewardsPartiallyClaimed};
+use crate::instructions::ClaimRewards;
+use crate::math::claimable;
+use crate::utils::{pay_stream_rewards, record_reward_payout, transfer_from_vault, update_rewards};
+
+/// Claims the primary reward and every active reward stream in one call.
+///
+/// Takes the `ClaimRewards` accounts plus, for each active stream in pool
+/// order, a `[stream_vault, user_reward_ata, reward_mint, token_program]`
+/// group in `remaining_accounts`.
+/// Streams with nothing owed or an empty vault are skipped rather than
+/// failing the whole claim.
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimRewards<'info>>) -> Result<()> {
+    require!(!ctx.accounts.pool.is_paused(PAUSE_CLAIM), StakingError::Paused);
+
+    let user = &mut ctx.accounts.user_stake;
+
+    update_rewards(user, &mut ctx.accounts.pool, Clock::get()?.unix_timestamp)?;
+
+    let (amount, remaining) = claimable(user.pending_rewards, ctx.accounts.reward_vault.amount);
+    record_reward_payout(&mut ctx.accounts.pool, amount)?;
+
+    let pool = &ctx.accounts.pool;
+    let pool_key = pool.key();
+    let mut paid_any = false;
+
+    if amount > 0 {
+        user.pending_rewards = remaining;
+        let received = transfer_from_vault(
+            pool,
+            ctx.accounts.reward_vault.to_account_info(),
+            ctx.accounts.reward_mint.to_account_info(),
+            ctx.accounts.user_reward_ata.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            amount,
+            ctx.accounts.reward_mint.decimals,
+        )?;
+        paid_any = true;
+
+        emit_cpi!(RewardsClaimed {
+            pool: pool_key,
+            user: user.owner,
+            reward_mint: pool.reward_mint,
+            amount,
+            received,
+            remaining,
+            timestamp: user.last_update,
+        });
+
+        if remaining > 0 {
+            emit_cpi!(RewardsPartiallyClaimed {
+                pool: pool_key,
+                user: user.owner,
+                reward_mint: pool.reward_mint,
+                paid: amount,
+                remaining,
+                timestamp: user.last_update,
+            });
+        }
+    }
+
+    let payouts = pay_stream_rewards(
+        &mut ctx.accounts.pool,
+        user,
+        ctx.remaining_accounts,
+        ctx.program_id,
+    )?;
+    for payout in &payouts {
+        emit_cpi!(RewardsClaimed {
+            pool: pool_key,
+            user: user.owner,
+            reward_mint: payout.mint,
+            amount: payout.amount,
+            received: payout.received,
+            remaining: payout.remaining,
+            timestamp: user.last_update,
+        });
+
+        if payout.remaining > 0 {
+            emit_cpi!(RewardsPartiallyClaimed {
+                pool: pool_key,
+                user: user.owner,
+                reward_mint: payout.mint,
+                paid: payout.amount,
+                remaining: payout.remaining,
+                timestamp: user.last_update,
+            });
+        }
+    }
+
+    require!(paid_any || !payouts.is_empty(), StakingError::NoRewardsAccrued);
+
+    Ok(())
+}
@@ -1,47 +1,14 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{TokenAccount, TokenInterface, Mint};
-use crate::state::UserStake::;
-use crate::errors::StakingError;
-
-#[derive(Accounts)]
-pub struct ClaimRewards<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
-
-    /// pool PDA
-    #[account(seeds = [b"pool", pool.stake_mint.as_ref(), pool.reward_mint.as_ref()], bump = pool.bump)]
-    pub pool: Account<'info, crate::state::Pool>,
-
-    /// user stake PDA
-    #[account(mut,
-        seeds = [b"user_stake", pool.key().as_ref(), user.key().as_ref()],
-        bump = user_stake_account.bump,
-        constraint = user_stake_account.owner == user.key() @ StakingError::Unauthorized
-    )]
-    pub user_stake_account: Account<'info, UserStakeAccount>,
-
-    /// reward vault token account PDA
-    #[account(mut, seeds = [b"reward_vault", pool.key().as_ref()], bump)]
-    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
-
-    /// PDA authority
-    pub reward_vault_authority: UncheckedAccount<'info>,
-
-    /// user reward ATA
-    #[account(mut)]
-    pub user_reward_ata: InterfaceAccount<'info, TokenAccount>,
-
-    pub reward_mint: InterfaceAccount<'info, Mint>,
-
-    pub token_program: Interface<'info, TokenInterface>,
-}
-use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
-use crate::state::{Pool, UserStake};
+use crate::state::{Pool, UserStake, PAUSE_CLAIM};
 use crate::errors::StakingError;
-use crate::utils::update_rewards;
+use crate::events::{RewardsClaimed, RewardsPartiallyClaimed, UserStakeClosed};
+use crate::math::claimable;
+use crate::utils::{record_reward_payout, transfer_from_vault, update_rewards};
+use crate::{USER_SEED, VAULT_REWARD_SEED};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct ClaimRewards<'info> {
     #[account(mut)]
@@ -52,67 +19,89 @@ pub struct ClaimRewards<'info> {
 
     #[account(
         mut,
-        seeds = [b"user_stake", pool.key().as_ref(), user.key().as_ref()],
+        seeds = [USER_SEED, pool.key().as_ref(), user.key().as_ref()],
         bump = user_stake.bump
     )]
     pub user_stake: Account<'info, UserStake>,
 
+    #[account(address = pool.reward_mint)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
     #[account(
         mut,
         token::mint = pool.reward_mint,
         token::authority = user
     )]
-    pub user_reward_ata: Account<'info, TokenAccount>,
+    pub user_reward_ata: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
-        seeds = [b"reward_vault", pool.key().as_ref()],
+        seeds = [VAULT_REWARD_SEED, pool.key().as_ref()],
         bump
     )]
-    pub reward_vault: Account<'info, TokenAccount>,
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
-pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
-    let user = &mut ctx.accounts.user_stake;
-
-    // Get pool data BEFORE mutable operations
-    let pool_bump = ctx.accounts.pool.bump;
-    let stake_mint = ctx.accounts.pool.stake_mint;
-    let reward_mint = ctx.accounts.pool.reward_mint;
+/// With `close` set, a position left empty is closed and its rent refunded to
+/// the user.
+pub fn handler(ctx: Context<ClaimRewards>, close: bool) -> Result<()> {
+    require!(!ctx.accounts.pool.is_paused(PAUSE_CLAIM), StakingError::Paused);
 
-    update_rewards(user, &ctx.accounts.pool)?;
-
-    let amount = user.pending_rewards as u64;
-    require!(amount > 0, StakingError::NoRewardsAccrued);
+    let user = &mut ctx.accounts.user_stake;
 
-    user.pending_rewards = 0;
+    update_rewards(user, &mut ctx.accounts.pool, Clock::get()?.unix_timestamp)?;
 
-    let seeds = &[
-        b"pool",
-        stake_mint.as_ref(),
-        reward_mint.as_ref(),
-        &[pool_bump],
-    ];
+    let pending = user.pending_rewards;
+    require!(pending > 0, StakingError::NoRewardsAccrued);
 
-    let signer = &[&seeds[..]];
+    // Pay out what the vault can cover; anything above that stays owed
+    require!(ctx.accounts.reward_vault.amount > 0, StakingError::RewardVaultDepleted);
 
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.reward_vault.to_account_info(),
-        to: ctx.accounts.user_reward_ata.to_account_info(),
-        authority: ctx.accounts.pool.to_account_info(),
-    };
+    let (amount, remaining) = claimable(pending, ctx.accounts.reward_vault.amount);
+    user.pending_rewards = remaining;
+    record_reward_payout(&mut ctx.accounts.pool, amount)?;
 
-    let cpi_ctx = CpiContext::new_with_signer(
+    let received = transfer_from_vault(
+        &ctx.accounts.pool,
+        ctx.accounts.reward_vault.to_account_info(),
+        ctx.accounts.reward_mint.to_account_info(),
+        ctx.accounts.user_reward_ata.to_account_info(),
         ctx.accounts.token_program.to_account_info(),
-        cpi_accounts,
-        signer,
-    );
-
-    token::transfer(cpi_ctx, amount)?;
-
-    user.last_update = Clock::get()?.unix_timestamp;
+        amount,
+        ctx.accounts.reward_mint.decimals,
+    )?;
+
+    emit_cpi!(RewardsClaimed {
+        pool: ctx.accounts.pool.key(),
+        user: user.owner,
+        reward_mint: ctx.accounts.pool.reward_mint,
+        amount,
+        received,
+        remaining,
+        timestamp: user.last_update,
+    });
+
+    if user.pending_rewards > 0 {
+        emit_cpi!(RewardsPartiallyClaimed {
+            pool: ctx.accounts.pool.key(),
+            user: user.owner,
+            reward_mint: ctx.accounts.pool.reward_mint,
+            paid: amount,
+            remaining: user.pending_rewards,
+            timestamp: user.last_update,
+        });
+    }
+
+    if close && user.is_empty() {
+        emit_cpi!(UserStakeClosed {
+            pool: ctx.accounts.pool.key(),
+            user: user.owner,
+            timestamp: user.last_update,
+        });
+        ctx.accounts.user_stake.close(ctx.accounts.user.to_account_info())?;
+    }
 
     Ok(())
 }
\ No newline at end of file
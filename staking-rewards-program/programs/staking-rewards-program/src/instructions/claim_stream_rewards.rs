@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::{Pool, UserStake, PAUSE_CLAIM};
+use crate::errors::StakingError;
+use crate::events::{RewardsClaimed, RewardsPartiallyClaimed};
+use crate::math::claimable;
+use crate::utils::{record_stream_payout, transfer_from_vault, update_rewards};
+use crate::{STREAM_VAULT_SEED, USER_SEED};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimStreamRewards<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        seeds = [USER_SEED, pool.key().as_ref(), user.key().as_ref()],
+        bump = user_stake.bump
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    #[account(address = stream_vault.mint)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = stream_vault.mint,
+        token::authority = user
+    )]
+    pub user_reward_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [STREAM_VAULT_SEED, pool.key().as_ref(), stream_vault.mint.as_ref()],
+        bump
+    )]
+    pub stream_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn handler(ctx: Context<ClaimStreamRewards>) -> Result<()> {
+    require!(!ctx.accounts.pool.is_paused(PAUSE_CLAIM), StakingError::Paused);
+
+    let mint = ctx.accounts.stream_vault.mint;
+    let index = ctx
+        .accounts
+        .pool
+        .find_reward_stream(&mint)
+        .ok_or(StakingError::RewardStreamNotFound)?;
+
+    let user = &mut ctx.accounts.user_stake;
+
+    update_rewards(user, &mut ctx.accounts.pool, Clock::get()?.unix_timestamp)?;
+
+    let pending = user.stream_rewards[index].pending_rewards;
+    require!(pending > 0, StakingError::NoRewardsAccrued);
+    require!(ctx.accounts.stream_vault.amount > 0, StakingError::RewardVaultDepleted);
+
+    let (amount, remaining) = claimable(pending, ctx.accounts.stream_vault.amount);
+    user.stream_rewards[index].pending_rewards = remaining;
+    record_stream_payout(&mut ctx.accounts.pool.reward_streams[index], amount)?;
+
+    let received = transfer_from_vault(
+        &ctx.accounts.pool,
+        ctx.accounts.stream_vault.to_account_info(),
+        ctx.accounts.reward_mint.to_account_info(),
+        ctx.accounts.user_reward_ata.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        amount,
+        ctx.accounts.reward_mint.decimals,
+    )?;
+
+    emit_cpi!(RewardsClaimed {
+        pool: ctx.accounts.pool.key(),
+        user: user.owner,
+        reward_mint: mint,
+        amount,
+        received,
+        remaining,
+        timestamp: user.last_update,
+    });
+
+    if remaining > 0 {
+        emit_cpi!(RewardsPartiallyClaimed {
+            pool: ctx.accounts.pool.key(),
+            user: user.owner,
+            reward_mint: mint,
+            paid: amount,
+            remaining,
+            timestamp: user.last_update,
+        });
+    }
+
+    Ok(())
+}
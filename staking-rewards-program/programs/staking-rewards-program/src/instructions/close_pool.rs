@@ -0,0 +1,150 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::Pool;
+use crate::errors::StakingError;
+use crate::events::PoolClosed;
+use crate::utils::close_vault;
+use crate::{STREAM_VAULT_SEED, VAULT_REWARD_SEED, VAULT_STAKE_SEED};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClosePool<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(mut, close = admin)]
+    pub pool: Account<'info, Pool>,
+
+    // Writable so Token-2022 withheld fees can be harvested into them
+    #[account(mut, address = pool.stake_mint)]
+    pub stake_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, address = pool.reward_mint)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = pool.stake_mint
+    )]
+    pub admin_stake_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = pool.reward_mint
+    )]
+    pub admin_reward_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_STAKE_SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_REWARD_SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program owning `stake_mint`; SPL Token or Token-2022.
+    pub stake_token_program: Interface<'info, TokenInterface>,
+    /// Token program owning `reward_mint`; SPL Token or Token-2022.
+    pub reward_token_program: Interface<'info, TokenInterface>,
+}
+
+/// Winds down a pool nobody has a claim on and returns all of its rent to the
+/// admin. Every position must be fully unstaked and unbonded, and every
+/// reward settled into a position must have been paid out. Whatever the
+/// vaults still hold, including reward periods that never emitted, goes to
+/// the admin before the vaults and the pool are closed.
+///
+/// Takes, for each active stream in pool order, a
+/// `[stream_vault, admin_reward_ata, reward_mint, token_program]` group in
+/// `remaining_accounts`; those vaults are swept and closed too.
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, ClosePool<'info>>) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+    require_keys_eq!(pool.admin, ctx.accounts.admin.key(), StakingError::Unauthorized);
+    require!(
+        pool.total_staked == 0 && pool.total_unbonding == 0,
+        StakingError::PoolNotEmpty
+    );
+    require!(
+        pool.rewards_owed == 0
+            && pool
+                .active_reward_streams()
+                .iter()
+                .all(|stream| stream.rewards_owed == 0),
+        StakingError::RewardsOutstanding
+    );
+
+    let pool_key = pool.key();
+    let admin = ctx.accounts.admin.to_account_info();
+
+    let streams = pool.active_reward_streams();
+    require!(
+        ctx.remaining_accounts.len() == streams.len() * 4,
+        StakingError::InvalidRewardStreamAccounts
+    );
+
+    for (stream, accounts) in streams.iter().zip(ctx.remaining_accounts.chunks_exact(4)) {
+        let [vault_info, ata_info, mint_info, token_program_info] = accounts else {
+            return err!(StakingError::InvalidRewardStreamAccounts);
+        };
+
+        let (expected_vault, _) = Pubkey::find_program_address(
+            &[STREAM_VAULT_SEED, pool_key.as_ref(), stream.mint.as_ref()],
+            ctx.program_id,
+        );
+        require_keys_eq!(vault_info.key(), expected_vault, StakingError::InvalidRewardStreamAccounts);
+
+        require_keys_eq!(mint_info.key(), stream.mint, StakingError::InvalidRewardStreamAccounts);
+        require_keys_eq!(
+            *mint_info.owner,
+            token_program_info.key(),
+            StakingError::InvalidRewardStreamAccounts
+        );
+
+        let mint = InterfaceAccount::<Mint>::try_from(mint_info)?;
+        let vault = InterfaceAccount::<TokenAccount>::try_from(vault_info)?;
+        let ata = InterfaceAccount::<TokenAccount>::try_from(ata_info)?;
+        require_keys_eq!(ata.mint, stream.mint, StakingError::InvalidRewardStreamAccounts);
+
+        close_vault(
+            pool,
+            &vault,
+            &mint,
+            ata_info.clone(),
+            admin.clone(),
+            token_program_info.clone(),
+        )?;
+    }
+
+    let stake_swept = close_vault(
+        pool,
+        &ctx.accounts.stake_vault,
+        &ctx.accounts.stake_mint,
+        ctx.accounts.admin_stake_ata.to_account_info(),
+        admin.clone(),
+        ctx.accounts.stake_token_program.to_account_info(),
+    )?;
+    let rewards_swept = close_vault(
+        pool,
+        &ctx.accounts.reward_vault,
+        &ctx.accounts.reward_mint,
+        ctx.accounts.admin_reward_ata.to_account_info(),
+        admin,
+        ctx.accounts.reward_token_program.to_account_info(),
+    )?;
+
+    emit_cpi!(PoolClosed {
+        pool: pool_key,
+        admin: ctx.accounts.admin.key(),
+        stake_swept,
+        rewards_swept,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
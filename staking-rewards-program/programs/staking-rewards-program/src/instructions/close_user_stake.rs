@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::state::UserStake;
+use crate::errors::StakingError;
+use crate::events::UserStakeClosed;
+use crate::USER_SEED;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseUserStake<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: only used to derive the position's address, so positions stay
+    /// closable after `close_pool`.
+    pub pool: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [USER_SEED, pool.key().as_ref(), user.key().as_ref()],
+        bump = user_stake.bump
+    )]
+    pub user_stake: Account<'info, UserStake>,
+}
+
+/// Closes an empty position and refunds its rent to the owner. A later
+/// `stake` opens a fresh one.
+pub fn handler(ctx: Context<CloseUserStake>) -> Result<()> {
+    require!(ctx.accounts.user_stake.is_empty(), StakingError::PositionNotEmpty);
+
+    emit_cpi!(UserStakeClosed {
+        pool: ctx.accounts.pool.key(),
+        user: ctx.accounts.user.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::{Pool, UserStake, PAUSE_CLAIM, PAUSE_STAKE};
+use crate::errors::StakingError;
+use crate::events::RewardsCompounded;
+use crate::math::claimable;
+use crate::utils::{record_reward_payout, sync_stake_weight, transfer_from_vault, update_rewards};
+use crate::{USER_SEED, VAULT_REWARD_SEED, VAULT_STAKE_SEED};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Compound<'info> {
+    pub user: Signer<'info>,
+
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        seeds = [USER_SEED, pool.key().as_ref(), user.key().as_ref()],
+        bump = user_stake.bump
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    #[account(address = pool.stake_mint)]
+    pub stake_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_STAKE_SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_REWARD_SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Restakes pending primary rewards without a round trip through the user's
+/// wallet. Only available when the pool stakes and rewards the same mint.
+pub fn handler(ctx: Context<Compound>) -> Result<()> {
+    require!(
+        !ctx.accounts.pool.is_paused(PAUSE_CLAIM | PAUSE_STAKE),
+        StakingError::Paused
+    );
+    require_keys_eq!(
+        ctx.accounts.pool.stake_mint,
+        ctx.accounts.pool.reward_mint,
+        StakingError::MintMismatch
+    );
+
+    let user = &mut ctx.accounts.user_stake;
+    let now = Clock::get()?.unix_timestamp;
+
+    update_rewards(user, &mut ctx.accounts.pool, now)?;
+
+    require!(user.pending_rewards > 0, StakingError::NoRewardsAccrued);
+    require!(ctx.accounts.reward_vault.amount > 0, StakingError::RewardVaultDepleted);
+
+    let (amount, remaining) = claimable(user.pending_rewards, ctx.accounts.reward_vault.amount);
+    user.pending_rewards = remaining;
+    record_reward_payout(&mut ctx.accounts.pool, amount)?;
+
+    // The stake vault is credited net of any transfer fee
+    let received = transfer_from_vault(
+        &ctx.accounts.pool,
+        ctx.accounts.reward_vault.to_account_info(),
+        ctx.accounts.stake_mint.to_account_info(),
+        ctx.accounts.stake_vault.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        amount,
+        ctx.accounts.stake_mint.decimals,
+    )?;
+
+    user.amount_staked = user
+        .amount_staked
+        .checked_add(received as u128)
+        .ok_or(StakingError::Overflow)?;
+
+    sync_stake_weight(user, &mut ctx.accounts.pool, now)?;
+
+    emit_cpi!(RewardsCompounded {
+        pool: ctx.accounts.pool.key(),
+        user: user.owner,
+        amount: received,
+        amount_staked: user.amount_staked,
+        total_staked: ctx.accounts.pool.total_staked,
+        timestamp: user.last_update,
+    });
+
+    Ok(())
+}
@@ -1,34 +1,52 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
 use crate::state::Pool;
 use crate::errors::StakingError;
+use crate::events::RewardsDeposited;
+use crate::utils::{next_reward_period, received_amount, update_pool};
+use crate::VAULT_REWARD_SEED;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct DepositRewards<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
 
-    #[account(mut)]
-    pub admin_reward_ata: Account<'info, TokenAccount>,
+    #[account(address = pool.reward_mint)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
 
     #[account(mut)]
-    pub reward_vault: Account<'info, TokenAccount>,
+    pub admin_reward_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_REWARD_SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
 
+    #[account(mut)]
     pub pool: Account<'info, Pool>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
-pub fn deposit_rewards(ctx: Context<DepositRewards>, amount: u64) -> Result<()> {
+/// Funds the reward vault. With a non-zero `duration` the deposit also starts
+/// a new reward period: the rate is derived from `amount` plus whatever was
+/// left undistributed in the current period, spread over `duration` seconds.
+/// With a zero `duration` the emission schedule is left untouched.
+pub fn handler(ctx: Context<DepositRewards>, amount: u64, duration: u64) -> Result<()> {
     require_keys_eq!(
         ctx.accounts.pool.admin,
         ctx.accounts.admin.key(),
         StakingError::Unauthorized
     );
 
-    let cpi_accounts = Transfer {
+    let vault_before = ctx.accounts.reward_vault.amount;
+    let cpi_accounts = TransferChecked {
         from: ctx.accounts.admin_reward_ata.to_account_info(),
+        mint: ctx.accounts.reward_mint.to_account_info(),
         to: ctx.accounts.reward_vault.to_account_info(),
         authority: ctx.accounts.admin.to_account_info(),
     };
@@ -38,7 +56,39 @@ pub fn deposit_rewards(ctx: Context<DepositRewards>, amount: u64) -> Result<()>
         cpi_accounts,
     );
 
-    token::transfer(cpi_ctx, amount)?;
+    token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.reward_mint.decimals)?;
+    let received = received_amount(&mut ctx.accounts.reward_vault, vault_before)?;
+
+    let pool = &mut ctx.accounts.pool;
+    let now = Clock::get()?.unix_timestamp;
+
+    if duration > 0 {
+        // Settle accrual under the old schedule first
+        update_pool(pool, now)?;
+
+        let (rate, period_finish) = next_reward_period(
+            pool.reward_rate_per_second,
+            pool.period_finish,
+            received,
+            duration,
+            ctx.accounts.reward_vault.amount,
+            now,
+        )?;
+
+        pool.reward_rate_per_second = rate;
+        pool.last_update_time = now;
+        pool.period_finish = period_finish;
+    }
+
+    emit_cpi!(RewardsDeposited {
+        pool: pool.key(),
+        reward_mint: pool.reward_mint,
+        amount: received,
+        vault_balance: ctx.accounts.reward_vault.amount,
+        reward_rate_per_second: pool.reward_rate_per_second,
+        period_finish: pool.period_finish,
+        timestamp: now,
+    });
 
     Ok(())
-}
\ No newline at end of file
+}
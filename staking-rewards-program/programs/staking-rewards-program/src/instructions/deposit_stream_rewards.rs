@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::state::Pool;
+use crate::errors::StakingError;
+use crate::events::RewardsDeposited;
+use crate::utils::{next_reward_period, received_amount, update_pool};
+use crate::STREAM_VAULT_SEED;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DepositStreamRewards<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(address = stream_vault.mint)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = stream_vault.mint,
+    )]
+    pub admin_reward_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [STREAM_VAULT_SEED, pool.key().as_ref(), stream_vault.mint.as_ref()],
+        bump
+    )]
+    pub stream_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Same semantics as `deposit_rewards`, applied to the stream whose mint
+/// matches `stream_vault`.
+pub fn handler(ctx: Context<DepositStreamRewards>, amount: u64, duration: u64) -> Result<()> {
+    require_keys_eq!(
+        ctx.accounts.pool.admin,
+        ctx.accounts.admin.key(),
+        StakingError::Unauthorized
+    );
+
+    let index = ctx
+        .accounts
+        .pool
+        .find_reward_stream(&ctx.accounts.stream_vault.mint)
+        .ok_or(StakingError::RewardStreamNotFound)?;
+
+    let vault_before = ctx.accounts.stream_vault.amount;
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.admin_reward_ata.to_account_info(),
+        mint: ctx.accounts.reward_mint.to_account_info(),
+        to: ctx.accounts.stream_vault.to_account_info(),
+        authority: ctx.accounts.admin.to_account_info(),
+    };
+
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+    );
+
+    token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.reward_mint.decimals)?;
+    let received = received_amount(&mut ctx.accounts.stream_vault, vault_before)?;
+
+    let pool = &mut ctx.accounts.pool;
+    let now = Clock::get()?.unix_timestamp;
+
+    if duration > 0 {
+        update_pool(pool, now)?;
+
+        let stream = &mut pool.reward_streams[index];
+        let (rate, period_finish) = next_reward_period(
+            stream.reward_rate_per_second,
+            stream.period_finish,
+            received,
+            duration,
+            ctx.accounts.stream_vault.amount,
+            now,
+        )?;
+
+        stream.reward_rate_per_second = rate;
+        stream.last_update_time = now;
+        stream.period_finish = period_finish;
+    }
+
+    let stream = &pool.reward_streams[index];
+    emit_cpi!(RewardsDeposited {
+        pool: pool.key(),
+        reward_mint: stream.mint,
+        amount: received,
+        vault_balance: ctx.accounts.stream_vault.amount,
+        reward_rate_per_second: stream.reward_rate_per_second,
+        period_finish: stream.period_finish,
+        timestamp: now,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,138 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::StakingError;
+use crate::events::EmergencyUnstaked;
+use crate::instructions::Unstake;
+use crate::state::{Pool, UnbondingEntry, UserStake, MAX_REWARD_STREAMS};
+use crate::utils::{earned, transfer_from_vault, update_pool};
+
+/// Returns the caller's full principal without settling rewards, so it stays
+/// recoverable even if the reward vault is drained or the reward math
+/// overflows. All pending rewards, on every stream, are forfeited. Principal
+/// under a running lock is not released early: it is queued as an unbonding
+/// entry that matures when the lock ends, or once the cooldown has elapsed if
+/// that is later. In pools with a cooldown the unlocked principal is queued
+/// too, instead of being paid out directly. Takes the same accounts as
+/// `unstake`.
+pub fn handler(ctx: Context<Unstake>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let user = &mut ctx.accounts.user_stake;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(user.amount_staked > 0, StakingError::InsufficientFunds);
+    let amount = u64::try_from(user.amount_staked).map_err(|_| StakingError::Overflow)?;
+    let unlocked = u64::try_from(user.unlocked_amount(now)).map_err(|_| StakingError::Overflow)?;
+    let lock_end = user.lock_end;
+
+    // Best effort: checkpoint the pool so remaining stakers are not credited
+    // with this position's share of the elapsed time. The checkpoint runs on
+    // a copy and is kept only if it completes, so a failure part way through
+    // leaves nothing half-updated. If the math itself is what is broken, carry
+    // on without it.
+    let mut checkpointed = Pool::clone(pool);
+    match update_pool(&mut checkpointed, now) {
+        Ok(()) => pool.set_inner(checkpointed),
+        Err(_) => msg!("Skipping pool checkpoint"),
+    }
+
+    // What the position gives up, settled or not, is no longer owed
+    let forfeited = earned(
+        user.effective_stake,
+        user.reward_per_token_paid,
+        user.pending_rewards,
+        pool.reward_per_token_stored,
+    )
+    .unwrap_or(user.pending_rewards);
+    pool.rewards_unpaid = pool.rewards_unpaid.saturating_sub(forfeited);
+    pool.rewards_owed = pool.rewards_owed.saturating_sub(user.pending_rewards);
+
+    let count = pool.reward_stream_count as usize;
+    let mut forfeited_stream_rewards = [0u128; MAX_REWARD_STREAMS];
+    for ((stream_forfeited, slot), stream) in forfeited_stream_rewards
+        .iter_mut()
+        .zip(&user.stream_rewards)
+        .zip(&pool.reward_streams[..count])
+    {
+        *stream_forfeited = earned(
+            user.effective_stake,
+            slot.reward_per_token_paid,
+            slot.pending_rewards,
+            stream.reward_per_token_stored,
+        )
+        .unwrap_or(slot.pending_rewards);
+    }
+
+    pool.total_staked = pool.total_staked.saturating_sub(user.effective_stake);
+
+    user.amount_staked = 0;
+    user.effective_stake = 0;
+    user.locked_amount = 0;
+    user.lock_end = 0;
+    user.lock_multiplier_bps = 0;
+    user.pending_rewards = 0;
+    user.reward_per_token_paid = pool.reward_per_token_stored;
+    for (slot, stream) in user.stream_rewards.iter_mut().zip(&mut pool.reward_streams[..count]) {
+        stream.rewards_owed = stream.rewards_owed.saturating_sub(slot.pending_rewards);
+        slot.pending_rewards = 0;
+        slot.reward_per_token_paid = stream.reward_per_token_stored;
+    }
+    user.last_update = now;
+
+    emit_cpi!(EmergencyUnstaked {
+        pool: pool.key(),
+        user: user.owner,
+        amount,
+        forfeited_rewards: forfeited,
+        forfeited_stream_rewards,
+        timestamp: now,
+    });
+
+    let cooldown = i64::try_from(pool.cooldown_seconds).map_err(|_| StakingError::Overflow)?;
+    let cooldown_end = now.saturating_add(cooldown);
+
+    queue_unbonding(user, pool, amount - unlocked, lock_end.max(cooldown_end))?;
+    if cooldown > 0 {
+        queue_unbonding(user, pool, unlocked, cooldown_end)?;
+        return Ok(());
+    }
+    if unlocked == 0 {
+        return Ok(());
+    }
+
+    transfer_from_vault(
+        &ctx.accounts.pool,
+        ctx.accounts.stake_vault.to_account_info(),
+        ctx.accounts.stake_mint.to_account_info(),
+        ctx.accounts.user_stake_ata.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        unlocked,
+        ctx.accounts.stake_mint.decimals,
+    )?;
+
+    Ok(())
+}
+
+/// Parks `amount` of principal in a free unbonding slot until `release_time`.
+fn queue_unbonding(
+    user: &mut UserStake,
+    pool: &mut Pool,
+    amount: u64,
+    release_time: i64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let slot = user
+        .unbonding
+        .iter()
+        .position(|entry| entry.amount == 0)
+        .ok_or(StakingError::UnbondingQueueFull)?;
+    user.unbonding[slot] = UnbondingEntry {
+        amount,
+        release_time,
+    };
+    pool.total_unbonding = pool.total_unbonding.saturating_add(amount as u128);
+
+    Ok(())
+}
@@ -0,0 +1,194 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::{Pool, UserStake, PAUSE_CLAIM, PAUSE_UNSTAKE};
+use crate::errors::StakingError;
+use crate::events::{RewardsClaimed, RewardsPartiallyClaimed, Unstaked, UserStakeClosed};
+use crate::math::claimable;
+use crate::utils::{
+    pay_stream_rewards, record_reward_payout, sync_stake_weight, transfer_from_vault, update_rewards,
+};
+use crate::{USER_SEED, VAULT_REWARD_SEED, VAULT_STAKE_SEED};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Exit<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        seeds = [USER_SEED, pool.key().as_ref(), user.key().as_ref()],
+        bump = user_stake.bump
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    #[account(address = pool.stake_mint)]
+    pub stake_mint: InterfaceAccount<'info, Mint>,
+    #[account(address = pool.reward_mint)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = pool.stake_mint,
+        token::authority = user
+    )]
+    pub user_stake_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = pool.reward_mint,
+        token::authority = user
+    )]
+    pub user_reward_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_STAKE_SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_REWARD_SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program owning `stake_mint`; SPL Token or Token-2022.
+    pub stake_token_program: Interface<'info, TokenInterface>,
+    /// Token program owning `reward_mint`; SPL Token or Token-2022.
+    pub reward_token_program: Interface<'info, TokenInterface>,
+}
+
+/// Leaves the pool in one call: settles rewards, returns the full principal
+/// and pays whatever primary and stream rewards the vaults can cover. Streams
+/// take the same `remaining_accounts` groups as `claim_all_rewards`. Unlike
+/// the claim instructions, having nothing to claim is not an error, so it is
+/// safe to batch. With `close` set, a position left empty is closed and its
+/// rent refunded to the user.
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, Exit<'info>>, close: bool) -> Result<()> {
+    require!(
+        !ctx.accounts.pool.is_paused(PAUSE_UNSTAKE | PAUSE_CLAIM),
+        StakingError::Paused
+    );
+    require!(ctx.accounts.pool.cooldown_seconds == 0, StakingError::CooldownRequired);
+
+    let user = &mut ctx.accounts.user_stake;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(
+        user.unlocked_amount(now) == user.amount_staked,
+        StakingError::StakeLocked
+    );
+
+    update_rewards(user, &mut ctx.accounts.pool, now)?;
+
+    let principal = u64::try_from(user.amount_staked).map_err(|_| StakingError::Overflow)?;
+    if principal > 0 {
+        let received = transfer_from_vault(
+            &ctx.accounts.pool,
+            ctx.accounts.stake_vault.to_account_info(),
+            ctx.accounts.stake_mint.to_account_info(),
+            ctx.accounts.user_stake_ata.to_account_info(),
+            ctx.accounts.stake_token_program.to_account_info(),
+            principal,
+            ctx.accounts.stake_mint.decimals,
+        )?;
+
+        let pool = &mut ctx.accounts.pool;
+        user.amount_staked = 0;
+        sync_stake_weight(user, pool, now)?;
+
+        emit_cpi!(Unstaked {
+            pool: pool.key(),
+            user: user.owner,
+            amount: principal,
+            received,
+            amount_staked: user.amount_staked,
+            total_staked: pool.total_staked,
+            timestamp: now,
+        });
+    }
+
+    let (amount, remaining) = claimable(user.pending_rewards, ctx.accounts.reward_vault.amount);
+    if amount > 0 {
+        user.pending_rewards = remaining;
+        record_reward_payout(&mut ctx.accounts.pool, amount)?;
+
+        let received = transfer_from_vault(
+            &ctx.accounts.pool,
+            ctx.accounts.reward_vault.to_account_info(),
+            ctx.accounts.reward_mint.to_account_info(),
+            ctx.accounts.user_reward_ata.to_account_info(),
+            ctx.accounts.reward_token_program.to_account_info(),
+            amount,
+            ctx.accounts.reward_mint.decimals,
+        )?;
+
+        emit_cpi!(RewardsClaimed {
+            pool: ctx.accounts.pool.key(),
+            user: user.owner,
+            reward_mint: ctx.accounts.pool.reward_mint,
+            amount,
+            received,
+            remaining,
+            timestamp: now,
+        });
+
+        if remaining > 0 {
+            emit_cpi!(RewardsPartiallyClaimed {
+                pool: ctx.accounts.pool.key(),
+                user: user.owner,
+                reward_mint: ctx.accounts.pool.reward_mint,
+                paid: amount,
+                remaining,
+                timestamp: now,
+            });
+        }
+    }
+
+    let payouts = pay_stream_rewards(
+        &mut ctx.accounts.pool,
+        user,
+        ctx.remaining_accounts,
+        ctx.program_id,
+    )?;
+    for payout in &payouts {
+        emit_cpi!(RewardsClaimed {
+            pool: ctx.accounts.pool.key(),
+            user: user.owner,
+            reward_mint: payout.mint,
+            amount: payout.amount,
+            received: payout.received,
+            remaining: payout.remaining,
+            timestamp: now,
+        });
+
+        if payout.remaining > 0 {
+            emit_cpi!(RewardsPartiallyClaimed {
+                pool: ctx.accounts.pool.key(),
+                user: user.owner,
+                reward_mint: payout.mint,
+                paid: payout.amount,
+                remaining: payout.remaining,
+                timestamp: now,
+            });
+        }
+    }
+
+    if close && user.is_empty() {
+        emit_cpi!(UserStakeClosed {
+            pool: ctx.accounts.pool.key(),
+            user: user.owner,
+            timestamp: now,
+        });
+        ctx.accounts.user_stake.close(ctx.accounts.user.to_account_info())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Pool, UserStake};
+use crate::errors::StakingError;
+use crate::events::LockExpired;
+use crate::utils::update_rewards;
+use crate::USER_SEED;
+
+/// Permissionless: drops the boost of a lock that has run out, so a position
+/// cannot keep earning at its lockup weight just by never being touched.
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExpireLock<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        seeds = [USER_SEED, pool.key().as_ref(), user_stake.owner.as_ref()],
+        bump = user_stake.bump
+    )]
+    pub user_stake: Account<'info, UserStake>,
+}
+
+pub fn handler(ctx: Context<ExpireLock>) -> Result<()> {
+    let user = &mut ctx.accounts.user_stake;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(
+        user.locked_amount > 0 && !user.is_locked(now),
+        StakingError::NoExpiredLock
+    );
+
+    // Settles at the boosted weight up to now, then releases the lock
+    update_rewards(user, &mut ctx.accounts.pool, now)?;
+
+    emit_cpi!(LockExpired {
+        pool: ctx.accounts.pool.key(),
+        user: user.owner,
+        effective_stake: user.effective_stake,
+        total_staked: ctx.accounts.pool.total_staked,
+        timestamp: now,
+    });
+
+    Ok(())
+}
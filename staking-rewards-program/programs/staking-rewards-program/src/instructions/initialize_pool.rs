@@ -1,21 +1,26 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, Mint, TokenAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
+use crate::events::PoolInitialized;
 use crate::state::Pool;
+use crate::{POOL_SEED, VAULT_REWARD_SEED, VAULT_STAKE_SEED};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct InitializePool<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
 
-    pub stake_mint: Account<'info, Mint>,
-    pub reward_mint: Account<'info, Mint>,
+    #[account(mint::token_program = stake_token_program)]
+    pub stake_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = reward_token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
 
     #[account(
         init,
         payer = admin,
         space = Pool::LEN,
-        seeds = [b"pool", stake_mint.key().as_ref(), reward_mint.key().as_ref()],
+        seeds = [POOL_SEED, stake_mint.key().as_ref(), reward_mint.key().as_ref()],
         bump
     )]
     pub pool: Account<'info, Pool>,
@@ -23,39 +28,64 @@ pub struct InitializePool<'info> {
     #[account(
         init,
         payer = admin,
-        seeds = [b"stake_vault", pool.key().as_ref()],
+        seeds = [VAULT_STAKE_SEED, pool.key().as_ref()],
         bump,
         token::mint = stake_mint,
         token::authority = pool,
+        token::token_program = stake_token_program,
     )]
-    pub stake_vault: Account<'info, TokenAccount>,
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         init,
         payer = admin,
-        seeds = [b"reward_vault", pool.key().as_ref()],
+        seeds = [VAULT_REWARD_SEED, pool.key().as_ref()],
         bump,
         token::mint = reward_mint,
         token::authority = pool,
+        token::token_program = reward_token_program,
     )]
-    pub reward_vault: Account<'info, TokenAccount>,
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
 
-    pub token_program: Program<'info, Token>,
+    /// Token program owning `stake_mint`; SPL Token or Token-2022.
+    pub stake_token_program: Interface<'info, TokenInterface>,
+    /// Token program owning `reward_mint`; SPL Token or Token-2022.
+    pub reward_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn initialize_pool(
+pub fn handler(
     ctx: Context<InitializePool>,
     reward_rate_per_second: u64,
 ) -> Result<()> {
     let pool = &mut ctx.accounts.pool;
 
     pool.admin = ctx.accounts.admin.key();
+    pool.pending_admin = Pubkey::default();
     pool.stake_mint = ctx.accounts.stake_mint.key();
     pool.reward_mint = ctx.accounts.reward_mint.key();
     pool.reward_rate_per_second = reward_rate_per_second;
     pool.total_staked = 0;
+    pool.reward_per_token_stored = 0;
+    pool.last_update_time = Clock::get()?.unix_timestamp;
+    pool.period_finish = 0;
+    pool.reward_stream_count = 0;
+    pool.lockup_tier_count = 0;
+    pool.cooldown_seconds = 0;
+    pool.total_unbonding = 0;
+    pool.paused = 0;
+    pool.rewards_unpaid = 0;
+    pool.rewards_owed = 0;
     pool.bump = ctx.bumps.pool;
 
+    emit_cpi!(PoolInitialized {
+        pool: pool.key(),
+        admin: pool.admin,
+        stake_mint: pool.stake_mint,
+        reward_mint: pool.reward_mint,
+        reward_rate_per_second,
+        timestamp: pool.last_update_time,
+    });
+
     Ok(())
-}
\ No newline at end of file
+}
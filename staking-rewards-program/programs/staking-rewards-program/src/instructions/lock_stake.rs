@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Pool, UserStake, PAUSE_STAKE};
+use crate::errors::StakingError;
+use crate::events::StakeLocked;
+use crate::utils::{sync_stake_weight, update_rewards};
+use crate::USER_SEED;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct LockStake<'info> {
+    pub user: Signer<'info>,
+
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        seeds = [USER_SEED, pool.key().as_ref(), user.key().as_ref()],
+        bump = user_stake.bump
+    )]
+    pub user_stake: Account<'info, UserStake>,
+}
+
+/// Locks `amount` of already-staked principal under lockup tier `tier`. Adding
+/// to an active lock restarts it under the new tier, which may not end before
+/// the current lock does.
+pub fn handler(ctx: Context<LockStake>, amount: u64, tier: u8) -> Result<()> {
+    require!(amount > 0, StakingError::ZeroAmount);
+    require!(!ctx.accounts.pool.is_paused(PAUSE_STAKE), StakingError::Paused);
+
+    let pool = &mut ctx.accounts.pool;
+    let user = &mut ctx.accounts.user_stake;
+
+    let tier = *pool
+        .active_lockup_tiers()
+        .get(tier as usize)
+        .ok_or(StakingError::InvalidLockupTier)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    update_rewards(user, pool, now)?;
+
+    require!(
+        user.unlocked_amount(now) >= amount as u128,
+        StakingError::InsufficientFunds
+    );
+
+    let lock_end = now.checked_add(tier.duration).ok_or(StakingError::Overflow)?;
+    if user.is_locked(now) {
+        require!(lock_end >= user.lock_end, StakingError::LockupTooShort);
+    }
+
+    user.locked_amount = user
+        .locked_amount
+        .checked_add(amount as u128)
+        .ok_or(StakingError::Overflow)?;
+    user.lock_end = lock_end;
+    user.lock_multiplier_bps = tier.multiplier_bps;
+
+    sync_stake_weight(user, pool, now)?;
+
+    emit_cpi!(StakeLocked {
+        pool: pool.key(),
+        user: user.owner,
+        locked_amount: user.locked_amount,
+        lock_end,
+        multiplier_bps: tier.multiplier_bps,
+        effective_stake: user.effective_stake,
+        total_staked: pool.total_staked,
+        timestamp: now,
+    });
+
+    Ok(())
+}
@@ -1,11 +1,57 @@
+// Each instruction module exposes its entry point as `handler`; lib.rs calls
+// them by path, so the glob re-exports below only need the account contexts.
+#![allow(ambiguous_glob_reexports)]
+
 pub mod initialize_pool;
 pub mod deposit_rewards;
 pub mod stake;
 pub mod unstake;
 pub mod claim_rewards;
+pub mod set_reward_rate;
+pub mod add_reward_stream;
+pub mod deposit_stream_rewards;
+pub mod claim_stream_rewards;
+pub mod claim_all_rewards;
+pub mod set_lockup_tiers;
+pub mod lock_stake;
+pub mod expire_lock;
+pub mod set_cooldown;
+pub mod request_unstake;
+pub mod withdraw_unbonded;
+pub mod emergency_unstake;
+pub mod pause;
+pub mod unpause;
+pub mod propose_admin;
+pub mod accept_admin;
+pub mod compound;
+pub mod view_pending_rewards;
+pub mod withdraw_excess_rewards;
+pub mod close_pool;
+pub mod close_user_stake;
+pub mod exit;
+pub mod stake_for;
 
 pub use initialize_pool::*;
 pub use deposit_rewards::*;
 pub use stake::*;
 pub use unstake::*;
-pub use claim_rewards::*;
\ No newline at end of file
+pub use claim_rewards::*;
+pub use set_reward_rate::*;
+pub use add_reward_stream::*;
+pub use deposit_stream_rewards::*;
+pub use claim_stream_rewards::*;
+pub use set_lockup_tiers::*;
+pub use lock_stake::*;
+pub use expire_lock::*;
+pub use set_cooldown::*;
+pub use request_unstake::*;
+pub use pause::*;
+pub use propose_admin::*;
+pub use accept_admin::*;
+pub use compound::*;
+pub use view_pending_rewards::*;
+pub use withdraw_excess_rewards::*;
+pub use close_pool::*;
+pub use close_user_stake::*;
+pub use exit::*;
+pub use stake_for::*;
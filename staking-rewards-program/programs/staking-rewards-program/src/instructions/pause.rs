@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Pool, PAUSE_ALL};
+use crate::errors::StakingError;
+use crate::events::PauseUpdated;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+}
+
+/// Sets the given `PAUSE_*` flags. `emergency_unstake` and
+/// `withdraw_unbonded` stay available so principal is always recoverable.
+pub fn handler(ctx: Context<SetPaused>, flags: u8) -> Result<()> {
+    update_paused(ctx, flags, true)
+}
+
+pub(crate) fn update_paused(ctx: Context<SetPaused>, flags: u8, paused: bool) -> Result<()> {
+    require_keys_eq!(
+        ctx.accounts.pool.admin,
+        ctx.accounts.admin.key(),
+        StakingError::Unauthorized
+    );
+    require!(flags != 0 && flags & !PAUSE_ALL == 0, StakingError::InvalidPauseFlags);
+
+    let pool = &mut ctx.accounts.pool;
+
+    if paused {
+        pool.paused |= flags;
+    } else {
+        pool.paused &= !flags;
+    }
+
+    emit_cpi!(PauseUpdated {
+        pool: pool.key(),
+        paused: pool.paused,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
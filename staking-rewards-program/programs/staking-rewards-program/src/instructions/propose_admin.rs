@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Pool;
+use crate::errors::StakingError;
+use crate::events::AdminProposed;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+}
+
+/// First step of an admin handover. `new_admin` only gains control once it
+/// signs `accept_admin`; proposing `Pubkey::default()` cancels a pending
+/// handover.
+pub fn handler(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+    require_keys_eq!(
+        ctx.accounts.pool.admin,
+        ctx.accounts.admin.key(),
+        StakingError::Unauthorized
+    );
+
+    let pool = &mut ctx.accounts.pool;
+    pool.pending_admin = new_admin;
+
+    emit_cpi!(AdminProposed {
+        pool: pool.key(),
+        admin: pool.admin,
+        pending_admin: new_admin,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
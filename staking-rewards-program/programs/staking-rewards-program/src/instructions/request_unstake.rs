@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Pool, UnbondingEntry, UserStake, PAUSE_UNSTAKE};
+use crate::errors::StakingError;
+use crate::events::UnstakeRequested;
+use crate::utils::{sync_stake_weight, update_rewards};
+use crate::USER_SEED;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RequestUnstake<'info> {
+    pub user: Signer<'info>,
+
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        seeds = [USER_SEED, pool.key().as_ref(), user.key().as_ref()],
+        bump = user_stake.bump
+    )]
+    pub user_stake: Account<'info, UserStake>,
+}
+
+/// Moves `amount` of unlocked principal out of the position into an
+/// unbonding entry. It stops earning immediately and can be withdrawn with
+/// `withdraw_unbonded` once the pool cooldown has elapsed.
+pub fn handler(ctx: Context<RequestUnstake>, amount: u64) -> Result<()> {
+    require!(amount > 0, StakingError::ZeroAmount);
+    require!(!ctx.accounts.pool.is_paused(PAUSE_UNSTAKE), StakingError::Paused);
+
+    let pool = &mut ctx.accounts.pool;
+    let user = &mut ctx.accounts.user_stake;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(
+        user.amount_staked >= amount as u128,
+        StakingError::InsufficientFunds
+    );
+    require!(
+        user.unlocked_amount(now) >= amount as u128,
+        StakingError::StakeLocked
+    );
+
+    let slot = user
+        .unbonding
+        .iter()
+        .position(|entry| entry.amount == 0)
+        .ok_or(StakingError::UnbondingQueueFull)?;
+
+    update_rewards(user, pool, now)?;
+
+    let cooldown = i64::try_from(pool.cooldown_seconds).map_err(|_| StakingError::Overflow)?;
+    let release_time = now.checked_add(cooldown).ok_or(StakingError::Overflow)?;
+    user.unbonding[slot] = UnbondingEntry {
+        amount,
+        release_time,
+    };
+
+    user.amount_staked = user
+        .amount_staked
+        .checked_sub(amount as u128)
+        .ok_or(StakingError::Overflow)?;
+    pool.total_unbonding = pool
+        .total_unbonding
+        .checked_add(amount as u128)
+        .ok_or(StakingError::Overflow)?;
+
+    sync_stake_weight(user, pool, now)?;
+
+    emit_cpi!(UnstakeRequested {
+        pool: pool.key(),
+        user: user.owner,
+        amount,
+        release_time,
+        amount_staked: user.amount_staked,
+        total_staked: pool.total_staked,
+        timestamp: now,
+    });
+
+    Ok(())
+}
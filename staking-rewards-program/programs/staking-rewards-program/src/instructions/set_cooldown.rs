@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Pool;
+use crate::errors::StakingError;
+use crate::events::CooldownUpdated;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetCooldown<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+}
+
+/// Changes the unbonding delay for future `request_unstake` calls. Entries
+/// already in the queue keep their release time.
+pub fn handler(ctx: Context<SetCooldown>, cooldown_seconds: u64) -> Result<()> {
+    require_keys_eq!(
+        ctx.accounts.pool.admin,
+        ctx.accounts.admin.key(),
+        StakingError::Unauthorized
+    );
+    i64::try_from(cooldown_seconds).map_err(|_| StakingError::Overflow)?;
+
+    let pool = &mut ctx.accounts.pool;
+    let old_cooldown_seconds = pool.cooldown_seconds;
+    pool.cooldown_seconds = cooldown_seconds;
+
+    emit_cpi!(CooldownUpdated {
+        pool: pool.key(),
+        old_cooldown_seconds,
+        new_cooldown_seconds: cooldown_seconds,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
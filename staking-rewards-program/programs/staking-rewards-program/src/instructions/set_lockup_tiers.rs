@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{LockupTier, Pool, BPS_DENOMINATOR, MAX_LOCKUP_TIERS};
+use crate::errors::StakingError;
+use crate::events::LockupTiersUpdated;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetLockupTiers<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+}
+
+/// Replaces the pool's lockup menu. Existing locks keep the multiplier they
+/// were taken at until they expire.
+pub fn handler(ctx: Context<SetLockupTiers>, tiers: Vec<LockupTier>) -> Result<()> {
+    require_keys_eq!(
+        ctx.accounts.pool.admin,
+        ctx.accounts.admin.key(),
+        StakingError::Unauthorized
+    );
+
+    require!(tiers.len() <= MAX_LOCKUP_TIERS, StakingError::InvalidLockupTier);
+    for tier in tiers.iter() {
+        require!(
+            tier.duration > 0 && tier.multiplier_bps as u128 >= BPS_DENOMINATOR,
+            StakingError::InvalidLockupTier
+        );
+    }
+
+    let pool = &mut ctx.accounts.pool;
+
+    pool.lockup_tiers = Default::default();
+    pool.lockup_tiers[..tiers.len()].copy_from_slice(&tiers);
+    pool.lockup_tier_count = tiers.len() as u8;
+
+    emit_cpi!(LockupTiersUpdated {
+        pool: pool.key(),
+        tiers,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::state::Pool;
+use crate::errors::StakingError;
+use crate::events::RewardRateUpdated;
+use crate::math::last_time_reward_applicable;
+use crate::utils::update_pool;
+use crate::VAULT_REWARD_SEED;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetRewardRate<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [VAULT_REWARD_SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+}
+
+/// Changes the emission rate from now on. Once a funded period has ended the
+/// pool returns to open-ended emission at the new rate; while one is running
+/// the new rate must stay covered by the vault until `period_finish`.
+pub fn handler(ctx: Context<SetRewardRate>, new_rate: u64) -> Result<()> {
+    require_keys_eq!(
+        ctx.accounts.pool.admin,
+        ctx.accounts.admin.key(),
+        StakingError::Unauthorized
+    );
+
+    let pool = &mut ctx.accounts.pool;
+    let now = Clock::get()?.unix_timestamp;
+
+    // Settle everything accrued so far at the old rate before switching
+    update_pool(pool, now)?;
+
+    if pool.period_finish != 0 && now >= pool.period_finish {
+        // The period is over: emit open-ended from now, not from its finish
+        pool.period_finish = 0;
+    } else if pool.period_finish != 0 {
+        let remaining = (pool.period_finish - now) as u128;
+        let available = (ctx.accounts.reward_vault.amount as u128)
+            .saturating_sub(pool.rewards_unpaid);
+        require!(
+            (new_rate as u128)
+                .checked_mul(remaining)
+                .ok_or(StakingError::Overflow)?
+                <= available,
+            StakingError::InsufficientFunds
+        );
+    }
+
+    // The new rate applies from now on. Time too short for the checkpoint to
+    // credit at the old rate is dropped; its rewards were never committed.
+    pool.last_update_time = last_time_reward_applicable(pool.period_finish, now);
+
+    let old_rate = pool.reward_rate_per_second;
+    pool.reward_rate_per_second = new_rate;
+
+    emit_cpi!(RewardRateUpdated {
+        pool: pool.key(),
+        old_rate,
+        new_rate,
+        timestamp: now,
+    });
+
+    Ok(())
+}
@@ -1,10 +1,13 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
-use crate::state::{Pool, UserStake};
+use crate::state::{Pool, UserStake, PAUSE_STAKE};
 use crate::errors::StakingError;
-use crate::utils::update_rewards;
+use crate::events::Staked;
+use crate::utils::{open_position, received_amount, sync_stake_weight, update_rewards};
+use crate::{USER_SEED, VAULT_STAKE_SEED};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Stake<'info> {
     #[account(mut)]
@@ -17,45 +20,53 @@ pub struct Stake<'info> {
         init_if_needed,
         payer = user,
         space = UserStake::LEN,
-        seeds = [b"user_stake", pool.key().as_ref(), user.key().as_ref()],
+        seeds = [USER_SEED, pool.key().as_ref(), user.key().as_ref()],
         bump
     )]
     pub user_stake: Account<'info, UserStake>,
 
+    #[account(address = pool.stake_mint)]
+    pub stake_mint: InterfaceAccount<'info, Mint>,
+
     #[account(
         mut,
         token::mint = pool.stake_mint,
         token::authority = user
     )]
-    pub user_stake_ata: Account<'info, TokenAccount>,
+    pub user_stake_ata: InterfaceAccount<'info, TokenAccount>,
 
-    #[account(mut)]
-    pub stake_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [VAULT_STAKE_SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+pub fn handler(ctx: Context<Stake>, amount: u64) -> Result<()> {
     require!(amount > 0, StakingError::ZeroAmount);
+    require!(!ctx.accounts.pool.is_paused(PAUSE_STAKE), StakingError::Paused);
 
     let user = &mut ctx.accounts.user_stake;
     let pool = &mut ctx.accounts.pool;
+    let now = Clock::get()?.unix_timestamp;
 
-    // Initialize user stake if needed
-    if user.owner == Pubkey::default() {
-        user.owner = ctx.accounts.user.key();
-        user.amount_staked = 0;
-        user.pending_rewards = 0;
-        user.last_update = Clock::get()?.unix_timestamp;
-        user.bump = ctx.bumps.user_stake;
+    // Open the position on first use. An empty one is opened afresh too, as
+    // its checkpoints may predate a pool re-created at the same address.
+    if user.is_empty() {
+        open_position(user, ctx.accounts.user.key(), ctx.bumps.user_stake, now);
     }
 
-    update_rewards(user, pool)?;
+    update_rewards(user, pool, now)?;
 
     // Transfer stake → vault
-    let cpi_accounts = Transfer {
+    let vault_before = ctx.accounts.stake_vault.amount;
+    let cpi_accounts = TransferChecked {
         from: ctx.accounts.user_stake_ata.to_account_info(),
+        mint: ctx.accounts.stake_mint.to_account_info(),
         to: ctx.accounts.stake_vault.to_account_info(),
         authority: ctx.accounts.user.to_account_info(),
     };
@@ -65,11 +76,27 @@ pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
         cpi_accounts,
     );
 
-    token::transfer(cpi_ctx, amount)?;
-
-    user.amount_staked += amount as u128;
-    pool.total_staked += amount as u128;
-    user.last_update = Clock::get()?.unix_timestamp;
+    token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.stake_mint.decimals)?;
+
+    // Credit what the vault received, net of any transfer fee
+    let received = received_amount(&mut ctx.accounts.stake_vault, vault_before)?;
+    require!(received > 0, StakingError::ZeroAmount);
+
+    user.amount_staked = user
+        .amount_staked
+        .checked_add(received as u128)
+        .ok_or(StakingError::Overflow)?;
+    sync_stake_weight(user, pool, now)?;
+
+    emit_cpi!(Staked {
+        pool: pool.key(),
+        user: user.owner,
+        funder: user.owner,
+        amount: received,
+        amount_staked: user.amount_staked,
+        total_staked: pool.total_staked,
+        timestamp: user.last_update,
+    });
 
     Ok(())
 }
\ No newline at end of file
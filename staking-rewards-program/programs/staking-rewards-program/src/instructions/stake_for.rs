@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::state::{Pool, UserStake, PAUSE_STAKE};
+use crate::errors::StakingError;
+use crate::events::Staked;
+use crate::utils::{open_position, received_amount, sync_stake_weight, update_rewards};
+use crate::{USER_SEED, VAULT_STAKE_SEED};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(beneficiary: Pubkey)]
+pub struct StakeFor<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init_if_needed,
+        payer = funder,
+        space = UserStake::LEN,
+        seeds = [USER_SEED, pool.key().as_ref(), beneficiary.as_ref()],
+        bump
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    #[account(address = pool.stake_mint)]
+    pub stake_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = pool.stake_mint,
+        token::authority = funder
+    )]
+    pub funder_stake_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_STAKE_SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Stakes the funder's tokens into `beneficiary`'s position, opening it if
+/// needed. The position belongs to the beneficiary alone: only they can
+/// unstake, claim or close it, and closing refunds the rent the funder paid to
+/// them.
+pub fn handler(ctx: Context<StakeFor>, beneficiary: Pubkey, amount: u64) -> Result<()> {
+    require!(amount > 0, StakingError::ZeroAmount);
+    require!(!ctx.accounts.pool.is_paused(PAUSE_STAKE), StakingError::Paused);
+    require_keys_neq!(beneficiary, Pubkey::default(), StakingError::InvalidBeneficiary);
+
+    let user = &mut ctx.accounts.user_stake;
+    let pool = &mut ctx.accounts.pool;
+    let now = Clock::get()?.unix_timestamp;
+
+    // An empty position is opened afresh, as in `stake`
+    if user.is_empty() {
+        open_position(user, beneficiary, ctx.bumps.user_stake, now);
+    }
+
+    update_rewards(user, pool, now)?;
+
+    let vault_before = ctx.accounts.stake_vault.amount;
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.funder_stake_ata.to_account_info(),
+        mint: ctx.accounts.stake_mint.to_account_info(),
+        to: ctx.accounts.stake_vault.to_account_info(),
+        authority: ctx.accounts.funder.to_account_info(),
+    };
+
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        cpi_accounts,
+    );
+
+    token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.stake_mint.decimals)?;
+
+    // Credit what the vault received, net of any transfer fee
+    let received = received_amount(&mut ctx.accounts.stake_vault, vault_before)?;
+    require!(received > 0, StakingError::ZeroAmount);
+
+    user.amount_staked = user
+        .amount_staked
+        .checked_add(received as u128)
+        .ok_or(StakingError::Overflow)?;
+    sync_stake_weight(user, pool, now)?;
+
+    emit_cpi!(Staked {
+        pool: pool.key(),
+        user: user.owner,
+        funder: ctx.accounts.funder.key(),
+        amount: received,
+        amount_staked: user.amount_staked,
+        total_staked: pool.total_staked,
+        timestamp: user.last_update,
+    });
+
+    Ok(())
+}
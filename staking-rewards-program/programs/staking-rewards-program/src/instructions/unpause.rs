@@ -0,0 +1,8 @@
+use anchor_lang::prelude::*;
+
+use crate::instructions::pause::{update_paused, SetPaused};
+
+/// Clears the given `PAUSE_*` flags. Takes the same accounts as `pause`.
+pub fn handler(ctx: Context<SetPaused>, flags: u8) -> Result<()> {
+    update_paused(ctx, flags, false)
+}
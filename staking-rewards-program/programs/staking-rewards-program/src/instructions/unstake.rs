@@ -1,10 +1,13 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
-use crate::state::{Pool, UserStake};
+use crate::state::{Pool, UserStake, PAUSE_UNSTAKE};
 use crate::errors::StakingError;
-use crate::utils::update_rewards;
+use crate::events::{Unstaked, UserStakeClosed};
+use crate::utils::{sync_stake_weight, transfer_from_vault, update_rewards};
+use crate::{USER_SEED, VAULT_STAKE_SEED};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Unstake<'info> {
     #[account(mut)]
@@ -15,73 +18,89 @@ pub struct Unstake<'info> {
 
     #[account(
         mut,
-        seeds = [b"user_stake", pool.key().as_ref(), user.key().as_ref()],
+        seeds = [USER_SEED, pool.key().as_ref(), user.key().as_ref()],
         bump = user_stake.bump
     )]
     pub user_stake: Account<'info, UserStake>,
 
+    #[account(address = pool.stake_mint)]
+    pub stake_mint: InterfaceAccount<'info, Mint>,
+
     #[account(
         mut,
         token::mint = pool.stake_mint,
         token::authority = user
     )]
-    pub user_stake_ata: Account<'info, TokenAccount>,
+    pub user_stake_ata: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
-        seeds = [b"stake_vault", pool.key().as_ref()],
+        seeds = [VAULT_STAKE_SEED, pool.key().as_ref()],
         bump
     )]
-    pub stake_vault: Account<'info, TokenAccount>,
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
-pub fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
+/// With `close` set, a position left empty is closed and its rent refunded to
+/// the user.
+pub fn handler(ctx: Context<Unstake>, amount: u64, close: bool) -> Result<()> {
     require!(amount > 0, StakingError::ZeroAmount);
+    require!(!ctx.accounts.pool.is_paused(PAUSE_UNSTAKE), StakingError::Paused);
+
+    require!(ctx.accounts.pool.cooldown_seconds == 0, StakingError::CooldownRequired);
 
     let user = &mut ctx.accounts.user_stake;
-    
+    let now = Clock::get()?.unix_timestamp;
+
     require!(
         user.amount_staked >= amount as u128,
         StakingError::InsufficientFunds
     );
+    require!(
+        user.unlocked_amount(now) >= amount as u128,
+        StakingError::StakeLocked
+    );
 
-    // Get pool data BEFORE mutable operations
-    let stake_bump = ctx.accounts.pool.bump;
-    let stake_mint = ctx.accounts.pool.stake_mint;
-    let reward_mint = ctx.accounts.pool.reward_mint;
-
-    update_rewards(user, &ctx.accounts.pool)?;
-
-    let seeds = &[
-        b"pool",
-        stake_mint.as_ref(),
-        reward_mint.as_ref(),
-        &[stake_bump],
-    ];
-
-    let signer = &[&seeds[..]];
-
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.stake_vault.to_account_info(),
-        to: ctx.accounts.user_stake_ata.to_account_info(),
-        authority: ctx.accounts.pool.to_account_info(),
-    };
+    update_rewards(user, &mut ctx.accounts.pool, now)?;
 
-    let cpi_ctx = CpiContext::new_with_signer(
+    let received = transfer_from_vault(
+        &ctx.accounts.pool,
+        ctx.accounts.stake_vault.to_account_info(),
+        ctx.accounts.stake_mint.to_account_info(),
+        ctx.accounts.user_stake_ata.to_account_info(),
         ctx.accounts.token_program.to_account_info(),
-        cpi_accounts,
-        signer,
-    );
-
-    token::transfer(cpi_ctx, amount)?;
+        amount,
+        ctx.accounts.stake_mint.decimals,
+    )?;
 
     // Now update the pool and user
     let pool = &mut ctx.accounts.pool;
-    user.amount_staked -= amount as u128;
-    pool.total_staked -= amount as u128;
-    user.last_update = Clock::get()?.unix_timestamp;
+    user.amount_staked = user
+        .amount_staked
+        .checked_sub(amount as u128)
+        .ok_or(StakingError::Overflow)?;
+    sync_stake_weight(user, pool, now)?;
+
+    emit_cpi!(Unstaked {
+        pool: pool.key(),
+        user: user.owner,
+        amount,
+        received,
+        amount_staked: user.amount_staked,
+        total_staked: pool.total_staked,
+        timestamp: now,
+    });
+
+    if close && user.is_empty() {
+        emit_cpi!(UserStakeClosed {
+            pool: pool.key(),
+            user: user.owner,
+            timestamp: now,
+        });
+        ctx.accounts.user_stake.close(ctx.accounts.user.to_account_info())?;
+    }
 
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Pool, UserStake};
+use crate::utils::{earned, reward_per_token, stream_reward_per_token};
+use crate::USER_SEED;
+
+#[derive(Accounts)]
+pub struct ViewPendingRewards<'info> {
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [USER_SEED, pool.key().as_ref(), user_stake.owner.as_ref()],
+        bump = user_stake.bump
+    )]
+    pub user_stake: Account<'info, UserStake>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PendingRewards {
+    pub reward_mint: Pubkey,
+    pub pending: u128,
+    pub streams: Vec<PendingStreamReward>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PendingStreamReward {
+    pub mint: Pubkey,
+    pub pending: u128,
+}
+
+/// Read-only: what the position would have pending, on the primary reward and
+/// every active stream, if it were settled right now. Nothing is written, so
+/// clients can get the number by simulating the transaction; it is returned
+/// through `set_return_data`.
+pub fn handler(ctx: Context<ViewPendingRewards>) -> Result<PendingRewards> {
+    pending_rewards(
+        &ctx.accounts.pool,
+        &ctx.accounts.user_stake,
+        Clock::get()?.unix_timestamp,
+    )
+}
+
+/// Rewards `user` would have pending if settled at `now`. Shared with
+/// off-chain estimators so they cannot drift from the program.
+pub fn pending_rewards(pool: &Pool, user: &UserStake, now: i64) -> Result<PendingRewards> {
+    // An empty position is owed nothing, even if its checkpoints predate a
+    // pool re-created at the same address
+    if user.is_empty() {
+        return Ok(PendingRewards {
+            reward_mint: pool.reward_mint,
+            pending: 0,
+            streams: pool
+                .active_reward_streams()
+                .iter()
+                .map(|stream| PendingStreamReward {
+                    mint: stream.mint,
+                    pending: 0,
+                })
+                .collect(),
+        });
+    }
+
+    // Settlement runs at the current weight, before an expired lock is released
+    let pending = earned(
+        user.effective_stake,
+        user.reward_per_token_paid,
+        user.pending_rewards,
+        reward_per_token(pool, now)?,
+    )?;
+
+    let streams = pool
+        .active_reward_streams()
+        .iter()
+        .zip(user.stream_rewards.iter())
+        .map(|(stream, slot)| {
+            Ok(PendingStreamReward {
+                mint: stream.mint,
+                pending: earned(
+                    user.effective_stake,
+                    slot.reward_per_token_paid,
+                    slot.pending_rewards,
+                    stream_reward_per_token(stream, pool.total_staked, now)?,
+                )?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(PendingRewards {
+        reward_mint: pool.reward_mint,
+        pending,
+        streams,
+    })
+}
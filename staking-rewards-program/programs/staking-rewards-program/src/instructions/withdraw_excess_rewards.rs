@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::Pool;
+use crate::errors::StakingError;
+use crate::events::ExcessRewardsWithdrawn;
+use crate::utils::{committed_rewards, transfer_from_vault, update_pool};
+use crate::VAULT_REWARD_SEED;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawExcessRewards<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    #[account(address = pool.reward_mint)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = pool.reward_mint
+    )]
+    pub admin_reward_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_REWARD_SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Returns reward tokens nobody is owed to the admin. The vault keeps
+/// everything emitted and not yet paid out, plus what the running period has
+/// still to emit; only the balance above that can leave.
+pub fn handler(ctx: Context<WithdrawExcessRewards>, amount: u64) -> Result<()> {
+    require!(amount > 0, StakingError::ZeroAmount);
+    require_keys_eq!(
+        ctx.accounts.pool.admin,
+        ctx.accounts.admin.key(),
+        StakingError::Unauthorized
+    );
+
+    let pool = &mut ctx.accounts.pool;
+    let now = Clock::get()?.unix_timestamp;
+    update_pool(pool, now)?;
+
+    let committed = committed_rewards(pool, now)?;
+    let excess = (ctx.accounts.reward_vault.amount as u128).saturating_sub(committed);
+    require!(amount as u128 <= excess, StakingError::RewardsCommitted);
+
+    transfer_from_vault(
+        &ctx.accounts.pool,
+        ctx.accounts.reward_vault.to_account_info(),
+        ctx.accounts.reward_mint.to_account_info(),
+        ctx.accounts.admin_reward_ata.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        amount,
+        ctx.accounts.reward_mint.decimals,
+    )?;
+
+    emit_cpi!(ExcessRewardsWithdrawn {
+        pool: ctx.accounts.pool.key(),
+        admin: ctx.accounts.admin.key(),
+        amount,
+        vault_balance: ctx.accounts.reward_vault.amount - amount,
+        timestamp: now,
+    });
+
+    Ok(())
+}
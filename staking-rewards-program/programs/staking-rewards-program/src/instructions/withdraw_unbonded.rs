@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::StakingError;
+use crate::events::UnbondedWithdrawn;
+use crate::instructions::Unstake;
+use crate::utils::transfer_from_vault;
+
+/// Pays out every unbonding entry whose cooldown has elapsed. Takes the same
+/// accounts as `unstake`.
+pub fn handler(ctx: Context<Unstake>) -> Result<()> {
+    let user = &mut ctx.accounts.user_stake;
+    let now = Clock::get()?.unix_timestamp;
+
+    let mut amount: u64 = 0;
+    for entry in user.unbonding.iter_mut() {
+        if entry.amount > 0 && entry.release_time <= now {
+            amount = amount.checked_add(entry.amount).ok_or(StakingError::Overflow)?;
+            *entry = Default::default();
+        }
+    }
+    require!(amount > 0, StakingError::NothingToWithdraw);
+
+    let pool = &mut ctx.accounts.pool;
+    pool.total_unbonding = pool
+        .total_unbonding
+        .checked_sub(amount as u128)
+        .ok_or(StakingError::Overflow)?;
+
+    let received = transfer_from_vault(
+        &ctx.accounts.pool,
+        ctx.accounts.stake_vault.to_account_info(),
+        ctx.accounts.stake_mint.to_account_info(),
+        ctx.accounts.user_stake_ata.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        amount,
+        ctx.accounts.stake_mint.decimals,
+    )?;
+
+    emit_cpi!(UnbondedWithdrawn {
+        pool: ctx.accounts.pool.key(),
+        user: ctx.accounts.user_stake.owner,
+        amount,
+        received,
+        total_unbonding: ctx.accounts.pool.total_unbonding,
+        timestamp: now,
+    });
+
+    Ok(())
+}
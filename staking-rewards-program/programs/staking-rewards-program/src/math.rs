@@ -0,0 +1,102 @@
+//! Reward arithmetic, independent of accounts and of the clock.
+//!
+//! Every function here is pure: the caller passes `now` explicitly, overflow
+//! yields `None` instead of an error, and only `core` is used, so the same code
+//! runs on-chain, in the test suites and in off-chain clients. All divisions
+//! round down, which always favours the pool: a staker is never credited more
+//! than the schedule emitted, and the remainder stays in the vault as dust.
+
+/// Fixed-point scale applied to reward-per-token accumulators.
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+/// `a * b / denominator`, rounded down.
+pub fn mul_div_floor(a: u128, b: u128, denominator: u128) -> Option<u128> {
+    a.checked_mul(b)?.checked_div(denominator)
+}
+
+/// Rewards stop accruing at `period_finish` once a funded period is running.
+/// A zero `period_finish` means the stream emits at a fixed rate indefinitely.
+pub fn last_time_reward_applicable(period_finish: i64, now: i64) -> i64 {
+    if period_finish == 0 {
+        now
+    } else {
+        now.min(period_finish)
+    }
+}
+
+/// Rewards a schedule releases to stakers between `last_update_time` and
+/// `now`. Nothing is released while the pool is empty.
+pub fn emitted(
+    rate: u64,
+    last_update_time: i64,
+    period_finish: i64,
+    total_staked: u128,
+    now: i64,
+) -> Option<u128> {
+    let elapsed = last_time_reward_applicable(period_finish, now).saturating_sub(last_update_time);
+
+    if total_staked == 0 || elapsed <= 0 {
+        return Some(0);
+    }
+    (rate as u128).checked_mul(elapsed as u128)
+}
+
+/// Advances a reward-per-token accumulator from `last_update_time` to `now`.
+/// Nothing accrues while the pool is empty. Rounds down.
+pub fn reward_per_token(
+    stored: u128,
+    rate: u64,
+    last_update_time: i64,
+    period_finish: i64,
+    total_staked: u128,
+    now: i64,
+) -> Option<u128> {
+    if total_staked == 0 {
+        return Some(stored);
+    }
+
+    let emitted = emitted(rate, last_update_time, period_finish, total_staked, now)?;
+    stored.checked_add(mul_div_floor(emitted, REWARD_PRECISION, total_staked)?)
+}
+
+/// Rewards owed to a stake of `effective_stake` whose accumulator snapshot is
+/// `reward_per_token_paid`, on top of what it already had pending. Rounds
+/// down.
+pub fn earned(
+    effective_stake: u128,
+    reward_per_token_paid: u128,
+    pending_rewards: u128,
+    reward_per_token: u128,
+) -> Option<u128> {
+    let delta = reward_per_token.checked_sub(reward_per_token_paid)?;
+    pending_rewards.checked_add(mul_div_floor(effective_stake, delta, REWARD_PRECISION)?)
+}
+
+/// Rewards a period ending at `period_finish` has yet to emit at `now`.
+pub fn undistributed(rate: u64, period_finish: i64, now: i64) -> Option<u128> {
+    if now >= period_finish {
+        return Some(0);
+    }
+    ((period_finish - now) as u128).checked_mul(rate as u128)
+}
+
+/// Rate that spreads `amount` plus whatever the current period has not
+/// emitted yet evenly over `duration` seconds. Rounds down, so the period never
+/// emits more than it was funded with.
+pub fn period_rate(
+    current_rate: u64,
+    period_finish: i64,
+    amount: u64,
+    duration: u64,
+    now: i64,
+) -> Option<u64> {
+    let total = (amount as u128).checked_add(undistributed(current_rate, period_finish, now)?)?;
+    u64::try_from(total.checked_div(duration as u128)?).ok()
+}
+
+/// Splits `pending` into what a vault holding `vault_balance` can pay now and
+/// what stays owed.
+pub fn claimable(pending: u128, vault_balance: u64) -> (u64, u128) {
+    let amount = pending.min(vault_balance as u128) as u64;
+    (amount, pending - amount as u128)
+}
@@ -0,0 +1,15 @@
+use anchor_lang::prelude::*;
+
+/// Multipliers are expressed in basis points; 10_000 is an unboosted 1x.
+pub const BPS_DENOMINATOR: u128 = 10_000;
+
+/// A lock duration a pool offers, and the stake weight it earns at.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct LockupTier {
+    pub duration: i64,
+    pub multiplier_bps: u16,
+}
+
+impl LockupTier {
+    pub const LEN: usize = 8 + 2;
+}
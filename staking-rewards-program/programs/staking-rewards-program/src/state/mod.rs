@@ -1,5 +1,11 @@
+pub mod lockup;
 pub mod pool;
+pub mod reward_stream;
+pub mod unbonding;
 pub mod user_stake;
 
+pub use lockup::*;
 pub use pool::*;
-pub use user_stake::*;
\ No newline at end of file
+pub use reward_stream::*;
+pub use unbonding::*;
+pub use user_stake::*;
@@ -1,15 +1,82 @@
 use anchor_lang::prelude::*;
 
+use super::{LockupTier, RewardStream};
+
+/// Maximum number of reward streams a pool can emit besides `reward_mint`.
+pub const MAX_REWARD_STREAMS: usize = 4;
+
+/// Maximum number of lockup tiers a pool can offer.
+pub const MAX_LOCKUP_TIERS: usize = 4;
+
+/// Bits of `Pool::paused`, each halting one group of user instructions.
+pub const PAUSE_STAKE: u8 = 1 << 0;
+pub const PAUSE_UNSTAKE: u8 = 1 << 1;
+pub const PAUSE_CLAIM: u8 = 1 << 2;
+pub const PAUSE_ALL: u8 = PAUSE_STAKE | PAUSE_UNSTAKE | PAUSE_CLAIM;
+
 #[account]
 pub struct Pool {
     pub admin: Pubkey,
+    /// Key proposed by `propose_admin`, or default when no handover is pending.
+    pub pending_admin: Pubkey,
     pub stake_mint: Pubkey,
     pub reward_mint: Pubkey,
     pub reward_rate_per_second: u64,
+    /// Sum of every user's `effective_stake`, i.e. lockup-weighted.
     pub total_staked: u128,
+    /// Rewards accrued per staked token, scaled by `math::REWARD_PRECISION`.
+    pub reward_per_token_stored: u128,
+    /// Timestamp `reward_per_token_stored` was last checkpointed at.
+    pub last_update_time: i64,
+    /// End of the current funded reward period, or 0 for open-ended emission.
+    pub period_finish: i64,
+    /// Number of slots in use in `reward_streams`.
+    pub reward_stream_count: u8,
+    pub reward_streams: [RewardStream; MAX_REWARD_STREAMS],
+    /// Number of slots in use in `lockup_tiers`.
+    pub lockup_tier_count: u8,
+    pub lockup_tiers: [LockupTier; MAX_LOCKUP_TIERS],
+    /// Delay between `request_unstake` and `withdraw_unbonded`. While non-zero,
+    /// instant `unstake` is disabled.
+    pub cooldown_seconds: u64,
+    /// Principal sitting in the stake vault in unbonding entries.
+    pub total_unbonding: u128,
+    /// `PAUSE_*` flags currently set by the admin.
+    pub paused: u8,
+    /// Primary rewards emitted to stakers and not yet paid out or forfeited.
+    /// Rounding dust keeps it slightly above what positions can claim, so
+    /// it is a safe bound on what the reward vault owes.
+    pub rewards_unpaid: u128,
+    /// Primary rewards settled into positions' `pending_rewards` and not yet
+    /// paid out. Exact, so zero means no position is owed anything.
+    pub rewards_owed: u128,
     pub bump: u8,
 }
 
 impl Pool {
-    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 16 + 1;
-}
\ No newline at end of file
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 32 + 8 + 16 + 16 + 8 + 8
+        + 1 + RewardStream::LEN * MAX_REWARD_STREAMS
+        + 1 + LockupTier::LEN * MAX_LOCKUP_TIERS
+        + 8 + 16
+        + 1
+        + 16 + 16
+        + 1;
+
+    pub fn is_paused(&self, flags: u8) -> bool {
+        self.paused & flags != 0
+    }
+
+    pub fn active_reward_streams(&self) -> &[RewardStream] {
+        &self.reward_streams[..self.reward_stream_count as usize]
+    }
+
+    pub fn active_lockup_tiers(&self) -> &[LockupTier] {
+        &self.lockup_tiers[..self.lockup_tier_count as usize]
+    }
+
+    pub fn find_reward_stream(&self, mint: &Pubkey) -> Option<usize> {
+        self.active_reward_streams()
+            .iter()
+            .position(|stream| stream.mint == *mint)
+    }
+}
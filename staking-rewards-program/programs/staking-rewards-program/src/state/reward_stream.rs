@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+/// An additional reward mint emitted alongside the pool's primary reward.
+/// Its tokens live in the `stream_vault` PDA derived from the pool and `mint`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct RewardStream {
+    pub mint: Pubkey,
+    pub reward_rate_per_second: u64,
+    pub reward_per_token_stored: u128,
+    pub last_update_time: i64,
+    pub period_finish: i64,
+    /// Stream rewards settled into positions and not yet paid out.
+    pub rewards_owed: u128,
+}
+
+impl RewardStream {
+    pub const LEN: usize = 32 + 8 + 16 + 8 + 8 + 16;
+}
+
+/// A user's accumulator for the `RewardStream` in the same slot on the pool.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct UserStreamReward {
+    pub reward_per_token_paid: u128,
+    pub pending_rewards: u128,
+}
+
+impl UserStreamReward {
+    pub const LEN: usize = 16 + 16;
+}
@@ -0,0 +1,13 @@
+use anchor_lang::prelude::*;
+
+/// Principal that has left a position and is waiting out the pool cooldown
+/// in the stake vault. An entry with a zero `amount` is a free slot.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct UnbondingEntry {
+    pub amount: u64,
+    pub release_time: i64,
+}
+
+impl UnbondingEntry {
+    pub const LEN: usize = 8 + 8;
+}
@@ -1,14 +1,70 @@
 use anchor_lang::prelude::*;
 
+use super::{UnbondingEntry, UserStreamReward, BPS_DENOMINATOR, MAX_REWARD_STREAMS};
+
+/// Maximum number of concurrent unbonding entries per position.
+pub const MAX_UNBONDING_ENTRIES: usize = 8;
+
 #[account]
 pub struct UserStake {
     pub owner: Pubkey,
+    /// Principal held in the stake vault, locked or not.
     pub amount_staked: u128,
+    /// Lockup-weighted stake this position earns rewards on.
+    pub effective_stake: u128,
+    /// Portion of `amount_staked` that cannot be unstaked before `lock_end`.
+    pub locked_amount: u128,
+    pub lock_end: i64,
+    pub lock_multiplier_bps: u16,
     pub pending_rewards: u128,
+    /// Snapshot of `Pool::reward_per_token_stored` at the last settlement.
+    pub reward_per_token_paid: u128,
     pub last_update: i64,
+    /// Per-stream accumulators, indexed like `Pool::reward_streams`.
+    pub stream_rewards: [UserStreamReward; MAX_REWARD_STREAMS],
+    pub unbonding: [UnbondingEntry; MAX_UNBONDING_ENTRIES],
     pub bump: u8,
 }
 
 impl UserStake {
-    pub const LEN: usize = 8 + 32 + 16 + 16 + 8 + 1;
-}
\ No newline at end of file
+    pub const LEN: usize = 8 + 32 + 16 + 16 + 16 + 8 + 2 + 16 + 16 + 8
+        + UserStreamReward::LEN * MAX_REWARD_STREAMS
+        + UnbondingEntry::LEN * MAX_UNBONDING_ENTRIES
+        + 1;
+
+    /// Whether the position holds nothing: no principal, no unbonding entries
+    /// and no rewards pending on any stream. Only then can it be closed.
+    pub fn is_empty(&self) -> bool {
+        self.amount_staked == 0
+            && self.pending_rewards == 0
+            && self.stream_rewards.iter().all(|slot| slot.pending_rewards == 0)
+            && self.unbonding.iter().all(|entry| entry.amount == 0)
+    }
+
+    pub fn is_locked(&self, now: i64) -> bool {
+        self.locked_amount > 0 && now < self.lock_end
+    }
+
+    /// Principal that can be withdrawn right now.
+    pub fn unlocked_amount(&self, now: i64) -> u128 {
+        if self.is_locked(now) {
+            self.amount_staked.saturating_sub(self.locked_amount)
+        } else {
+            self.amount_staked
+        }
+    }
+
+    /// Stake weight implied by the current principal and lock, at `now`.
+    pub fn weighted_stake(&self, now: i64) -> Option<u128> {
+        if !self.is_locked(now) {
+            return Some(self.amount_staked);
+        }
+
+        let boosted = self
+            .locked_amount
+            .checked_mul(self.lock_multiplier_bps as u128)?
+            / BPS_DENOMINATOR;
+
+        self.unlocked_amount(now).checked_add(boosted)
+    }
+}
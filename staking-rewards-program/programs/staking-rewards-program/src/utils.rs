@@ -1,32 +1,464 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+};
+use anchor_spl::token_2022_extensions::{harvest_withheld_tokens_to_mint, HarvestWithheldTokensToMint};
+use anchor_spl::token_interface::{self, CloseAccount, Mint, TokenAccount, TransferChecked};
 
-use crate::state::{UserStake, Pool};
+use crate::state::{UserStake, Pool, RewardStream, MAX_REWARD_STREAMS};
 use crate::errors::StakingError;
+use crate::math;
+use crate::{POOL_SEED, STREAM_VAULT_SEED};
 
-pub fn update_rewards(user: &mut UserStake, pool: &Pool) -> Result<()> {
-    let now = Clock::get()?.unix_timestamp;
+pub fn reward_per_token(pool: &Pool, now: i64) -> Result<u128> {
+    Ok(math::reward_per_token(
+        pool.reward_per_token_stored,
+        pool.reward_rate_per_second,
+        pool.last_update_time,
+        pool.period_finish,
+        pool.total_staked,
+        now,
+    )
+    .ok_or(StakingError::Overflow)?)
+}
 
-    if user.amount_staked == 0 || pool.total_staked == 0 {
-        user.last_update = now;
-        return Ok(());
+pub fn stream_reward_per_token(stream: &RewardStream, total_staked: u128, now: i64) -> Result<u128> {
+    Ok(math::reward_per_token(
+        stream.reward_per_token_stored,
+        stream.reward_rate_per_second,
+        stream.last_update_time,
+        stream.period_finish,
+        total_staked,
+        now,
+    )
+    .ok_or(StakingError::Overflow)?)
+}
+
+pub fn earned(
+    effective_stake: u128,
+    reward_per_token_paid: u128,
+    pending_rewards: u128,
+    reward_per_token: u128,
+) -> Result<u128> {
+    Ok(math::earned(
+        effective_stake,
+        reward_per_token_paid,
+        pending_rewards,
+        reward_per_token,
+    )
+    .ok_or(StakingError::Overflow)?)
+}
+
+/// Checkpoints the primary and every active stream accumulator up to `now`.
+pub fn update_pool(pool: &mut Pool, now: i64) -> Result<()> {
+    let emitted = math::emitted(
+        pool.reward_rate_per_second,
+        pool.last_update_time,
+        pool.period_finish,
+        pool.total_staked,
+        now,
+    )
+    .ok_or(StakingError::Overflow)?;
+    let stored = reward_per_token(pool, now)?;
+
+    // While the emission is too small to move the accumulator, leave the
+    // checkpoint where it is so the time keeps counting. Otherwise frequent
+    // checkpoints would round every staker's accrual down to nothing.
+    if emitted == 0 || stored > pool.reward_per_token_stored {
+        pool.rewards_unpaid = pool
+            .rewards_unpaid
+            .checked_add(emitted)
+            .ok_or(StakingError::Overflow)?;
+        pool.reward_per_token_stored = stored;
+        pool.last_update_time = math::last_time_reward_applicable(pool.period_finish, now);
     }
 
-    let elapsed = now - user.last_update;
+    let total_staked = pool.total_staked;
+    let count = pool.reward_stream_count as usize;
+    for stream in pool.reward_streams[..count].iter_mut() {
+        let emitted = math::emitted(
+            stream.reward_rate_per_second,
+            stream.last_update_time,
+            stream.period_finish,
+            total_staked,
+            now,
+        )
+        .ok_or(StakingError::Overflow)?;
+        let stored = stream_reward_per_token(stream, total_staked, now)?;
 
-    if elapsed <= 0 {
-        return Ok(());
+        if emitted == 0 || stored > stream.reward_per_token_stored {
+            stream.reward_per_token_stored = stored;
+            stream.last_update_time = math::last_time_reward_applicable(stream.period_finish, now);
+        }
     }
 
-    let reward =
-        (user.amount_staked * pool.reward_rate_per_second as u128 * elapsed as u128)
-            / pool.total_staked;
+    Ok(())
+}
 
-    user.pending_rewards = user
-        .pending_rewards
-        .checked_add(reward)
+/// Fills in a position `init_if_needed` has just created for `owner`, or
+/// resets an empty one. Only safe while the position holds nothing.
+pub fn open_position(user: &mut UserStake, owner: Pubkey, bump: u8, now: i64) {
+    user.owner = owner;
+    user.amount_staked = 0;
+    user.effective_stake = 0;
+    user.locked_amount = 0;
+    user.lock_end = 0;
+    user.lock_multiplier_bps = 0;
+    user.pending_rewards = 0;
+    user.reward_per_token_paid = 0;
+    user.stream_rewards = Default::default();
+    user.unbonding = Default::default();
+    user.last_update = now;
+    user.bump = bump;
+}
+
+/// Checkpoints the pool, then settles the user's share of every stream into
+/// its pending rewards. Must run before any change to `amount_staked` or
+/// `total_staked`. Also releases the boost of a lock that has expired.
+pub fn update_rewards(user: &mut UserStake, pool: &mut Pool, now: i64) -> Result<()> {
+    update_pool(pool, now)?;
+
+    // An empty position has nothing to settle, and its checkpoints may
+    // predate a pool re-created at the same address, so start it afresh
+    if user.is_empty() {
+        open_position(user, user.owner, user.bump, now);
+    }
+
+    let pending = earned(
+        user.effective_stake,
+        user.reward_per_token_paid,
+        user.pending_rewards,
+        pool.reward_per_token_stored,
+    )?;
+    pool.rewards_owed = pool
+        .rewards_owed
+        .checked_add(pending - user.pending_rewards)
         .ok_or(StakingError::Overflow)?;
+    user.pending_rewards = pending;
+    user.reward_per_token_paid = pool.reward_per_token_stored;
+
+    let count = pool.reward_stream_count as usize;
+    for (slot, stream) in user.stream_rewards.iter_mut().zip(&mut pool.reward_streams[..count]) {
+        let pending = earned(
+            user.effective_stake,
+            slot.reward_per_token_paid,
+            slot.pending_rewards,
+            stream.reward_per_token_stored,
+        )?;
+        stream.rewards_owed = stream
+            .rewards_owed
+            .checked_add(pending - slot.pending_rewards)
+            .ok_or(StakingError::Overflow)?;
+        slot.pending_rewards = pending;
+        slot.reward_per_token_paid = stream.reward_per_token_stored;
+    }
 
     user.last_update = now;
 
+    sync_stake_weight(user, pool, now)
+}
+
+/// Recomputes the user's lockup-weighted stake and moves `Pool::total_staked`
+/// by the difference. Call after every change to a position's principal or
+/// lock, once rewards have been settled.
+pub fn sync_stake_weight(user: &mut UserStake, pool: &mut Pool, now: i64) -> Result<()> {
+    if user.locked_amount > 0 && !user.is_locked(now) {
+        user.locked_amount = 0;
+        user.lock_end = 0;
+        user.lock_multiplier_bps = 0;
+    }
+
+    let weighted = user.weighted_stake(now).ok_or(StakingError::Overflow)?;
+
+    pool.total_staked = pool
+        .total_staked
+        .checked_sub(user.effective_stake)
+        .and_then(|v| v.checked_add(weighted))
+        .ok_or(StakingError::Overflow)?;
+    user.effective_stake = weighted;
+
+    Ok(())
+}
+
+/// Records `amount` of primary rewards leaving the reward vault.
+pub fn record_reward_payout(pool: &mut Pool, amount: u64) -> Result<()> {
+    pool.rewards_unpaid = pool
+        .rewards_unpaid
+        .checked_sub(amount as u128)
+        .ok_or(StakingError::Overflow)?;
+    pool.rewards_owed = pool
+        .rewards_owed
+        .checked_sub(amount as u128)
+        .ok_or(StakingError::Overflow)?;
+    Ok(())
+}
+
+/// Records `amount` of a stream's rewards leaving its vault.
+pub fn record_stream_payout(stream: &mut RewardStream, amount: u64) -> Result<()> {
+    stream.rewards_owed = stream
+        .rewards_owed
+        .checked_sub(amount as u128)
+        .ok_or(StakingError::Overflow)?;
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Primary reward tokens the vault must keep at `now`: everything emitted and
+/// not yet paid out, plus what the running period has still to emit. Expects
+/// the pool checkpointed to `now`.
+pub fn committed_rewards(pool: &Pool, now: i64) -> Result<u128> {
+    Ok(math::undistributed(pool.reward_rate_per_second, pool.period_finish, now)
+        .and_then(|future| future.checked_add(pool.rewards_unpaid))
+        .ok_or(StakingError::Overflow)?)
+}
+
+/// Derives the rate for a new reward period funded with `amount`, rolling over
+/// whatever the current period has not distributed yet. Returns the new rate
+/// and period end.
+pub fn next_reward_period(
+    current_rate: u64,
+    period_finish: i64,
+    amount: u64,
+    duration: u64,
+    vault_balance: u64,
+    now: i64,
+) -> Result<(u64, i64)> {
+    let rate = math::period_rate(current_rate, period_finish, amount, duration, now)
+        .ok_or(StakingError::Overflow)?;
+    require!(rate > 0, StakingError::RewardRateTooLow);
+
+    // Never promise more over the period than the vault actually holds
+    require!(
+        (rate as u128) * (duration as u128) <= vault_balance as u128,
+        StakingError::InsufficientFunds
+    );
+
+    let duration = i64::try_from(duration).map_err(|_| StakingError::Overflow)?;
+    let finish = now.checked_add(duration).ok_or(StakingError::Overflow)?;
+
+    Ok((rate, finish))
+}
+
+/// Reloads `vault` after an inbound transfer and returns how much its balance
+/// actually grew. Transfer-fee mints deliver less than the amount sent, so
+/// anything credited to the pool must be measured this way.
+pub fn received_amount(
+    vault: &mut InterfaceAccount<'_, TokenAccount>,
+    balance_before: u64,
+) -> Result<u64> {
+    vault.reload()?;
+    Ok(vault
+        .amount
+        .checked_sub(balance_before)
+        .ok_or(StakingError::Overflow)?)
+}
+
+/// Fee the Token-2022 transfer-fee extension withholds from a transfer of
+/// `amount` in the current epoch. Zero for SPL Token mints and for mints
+/// without the extension.
+pub fn transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(0);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let Ok(config) = mint.get_extension::<TransferFeeConfig>() else {
+        return Ok(0);
+    };
+
+    Ok(config
+        .calculate_epoch_fee(Clock::get()?.epoch, amount)
+        .ok_or(StakingError::Overflow)?)
+}
+
+/// Transfers `amount` out of a pool-owned vault, signing with the pool PDA.
+/// Works for both SPL Token and Token-2022 vaults. The vault is always debited
+/// the full `amount`; any transfer fee is withheld from the recipient. Returns
+/// what the recipient is credited after that fee.
+pub fn transfer_from_vault<'info>(
+    pool: &Account<'info, Pool>,
+    vault: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    amount: u64,
+    decimals: u8,
+) -> Result<u64> {
+    let bump = [pool.bump];
+    let seeds = pool_seeds(pool, &bump);
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = TransferChecked {
+        from: vault,
+        mint,
+        to,
+        authority: pool.to_account_info(),
+    };
+
+    let fee = transfer_fee(&cpi_accounts.mint, amount)?;
+    let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signer);
+
+    token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
+
+    Ok(amount - fee)
+}
+
+/// A stream payout made by `pay_stream_rewards`, for the caller to report.
+pub struct StreamPayout {
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub received: u64,
+    pub remaining: u128,
+}
+
+/// Pays `user` what each active stream's vault can cover of their settled
+/// stream rewards. `remaining_accounts` must hold, for each active stream in
+/// pool order, a `[stream_vault, user_reward_ata, reward_mint, token_program]`
+/// group. Streams with nothing owed or an empty vault are skipped. Returns the
+/// payouts made.
+pub fn pay_stream_rewards<'info>(
+    pool: &mut Account<'info, Pool>,
+    user: &mut UserStake,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    program_id: &Pubkey,
+) -> Result<Vec<StreamPayout>> {
+    let pool_key = pool.key();
+    let count = pool.reward_stream_count as usize;
+    require!(
+        remaining_accounts.len() == count * 4,
+        StakingError::InvalidRewardStreamAccounts
+    );
+
+    let mut payouts = Vec::new();
+    let mut amounts = [0u64; MAX_REWARD_STREAMS];
+    for (index, accounts) in remaining_accounts.chunks_exact(4).enumerate() {
+        let [vault_info, ata_info, mint_info, token_program_info] = accounts else {
+            return err!(StakingError::InvalidRewardStreamAccounts);
+        };
+        let stream_mint = pool.reward_streams[index].mint;
+
+        let (expected_vault, _) = Pubkey::find_program_address(
+            &[STREAM_VAULT_SEED, pool_key.as_ref(), stream_mint.as_ref()],
+            program_id,
+        );
+        require_keys_eq!(vault_info.key(), expected_vault, StakingError::InvalidRewardStreamAccounts);
+
+        require_keys_eq!(mint_info.key(), stream_mint, StakingError::InvalidRewardStreamAccounts);
+        require_keys_eq!(
+            *mint_info.owner,
+            token_program_info.key(),
+            StakingError::InvalidRewardStreamAccounts
+        );
+
+        let mint = InterfaceAccount::<Mint>::try_from(mint_info)?;
+        let vault = InterfaceAccount::<TokenAccount>::try_from(vault_info)?;
+        let ata = InterfaceAccount::<TokenAccount>::try_from(ata_info)?;
+        require!(
+            ata.mint == stream_mint && ata.owner == user.owner,
+            StakingError::InvalidRewardStreamAccounts
+        );
+
+        let slot = &mut user.stream_rewards[index];
+        let (amount, remaining) = math::claimable(slot.pending_rewards, vault.amount);
+        if amount == 0 {
+            continue;
+        }
+        slot.pending_rewards = remaining;
+        amounts[index] = amount;
+
+        let received = transfer_from_vault(
+            pool,
+            vault_info.clone(),
+            mint_info.clone(),
+            ata_info.clone(),
+            token_program_info.clone(),
+            amount,
+            mint.decimals,
+        )?;
+
+        payouts.push(StreamPayout {
+            mint: stream_mint,
+            amount,
+            received,
+            remaining,
+        });
+    }
+
+    for (stream, amount) in pool.reward_streams.iter_mut().zip(amounts) {
+        record_stream_payout(stream, amount)?;
+    }
+
+    Ok(payouts)
+}
+
+/// Empties a pool-owned vault into `to` and closes it, sending its rent to
+/// `rent_to`. Token-2022 fees withheld in the vault are harvested to the mint
+/// first, since an account still holding them cannot be closed. Returns the
+/// amount swept.
+pub fn close_vault<'info>(
+    pool: &Account<'info, Pool>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: AccountInfo<'info>,
+    rent_to: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+) -> Result<u64> {
+    let amount = vault.amount;
+    if amount > 0 {
+        transfer_from_vault(
+            pool,
+            vault.to_account_info(),
+            mint.to_account_info(),
+            to,
+            token_program.clone(),
+            amount,
+            mint.decimals,
+        )?;
+    }
+
+    if has_transfer_fee(&mint.to_account_info())? {
+        let cpi_accounts = HarvestWithheldTokensToMint {
+            token_program_id: token_program.clone(),
+            mint: mint.to_account_info(),
+        };
+        harvest_withheld_tokens_to_mint(
+            CpiContext::new(token_program.clone(), cpi_accounts),
+            vec![vault.to_account_info()],
+        )?;
+    }
+
+    let bump = [pool.bump];
+    let seeds = pool_seeds(pool, &bump);
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = CloseAccount {
+        account: vault.to_account_info(),
+        destination: rent_to,
+        authority: pool.to_account_info(),
+    };
+    token_interface::close_account(CpiContext::new_with_signer(token_program, cpi_accounts, signer))?;
+
+    Ok(amount)
+}
+
+/// Whether `mint` is a Token-2022 mint with the transfer-fee extension.
+fn has_transfer_fee(mint: &AccountInfo) -> Result<bool> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(false);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    Ok(mint.get_extension::<TransferFeeConfig>().is_ok())
+}
+
+/// Signer seeds of the pool PDA, which owns every vault.
+fn pool_seeds<'a>(pool: &'a Pool, bump: &'a [u8; 1]) -> [&'a [u8]; 4] {
+    [
+        POOL_SEED,
+        pool.stake_mint.as_ref(),
+        pool.reward_mint.as_ref(),
+        bump,
+    ]
+}
@@ -0,0 +1,603 @@
+//! LiteSVM harness that runs the compiled program.
+//!
+//! Every operation is a signed transaction against the SBF build from
+//! `anchor build` (or `$SBF_OUT_DIR`), so account constraints, token CPIs and
+//! the handlers themselves all execute as they would on a validator. Mints and
+//! token accounts are plain SPL Token accounts written straight into the VM.
+//! Time only moves when a test warps the `Clock` sysvar, and accounts are
+//! decoded back out of the VM for assertions.
+
+#![allow(dead_code)]
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::error::InstructionError;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token;
+use litesvm::LiteSVM;
+use solana_account::Account;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+use solana_transaction_error::TransactionError;
+use staking_rewards_program::{LockupTier, PendingRewards, Pool, UserStake, ID};
+use staking_rewards_sdk::instructions::{self, StreamClaim};
+use staking_rewards_sdk::{decode_pool, decode_user_stake, PoolKeys};
+
+/// Cluster time every harness starts at.
+pub const START: i64 = 1_700_000_000;
+
+/// Stake tokens every new wallet starts with.
+pub const WALLET_BALANCE: u64 = 1_000_000_000_000;
+
+const DECIMALS: u8 = 6;
+const LAMPORTS: u64 = 100_000_000_000;
+
+/// Custom error code of a failed transaction.
+pub type Result<T> = std::result::Result<T, u32>;
+
+/// Path of the compiled program: `$SBF_OUT_DIR`, or `target/deploy` of this
+/// workspace.
+fn program_path() -> PathBuf {
+    std::env::var_os("SBF_OUT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../target/deploy"))
+        .join("staking_rewards_program.so")
+}
+
+struct Wallet {
+    keypair: Keypair,
+    stake_ata: Pubkey,
+    reward_ata: Pubkey,
+    /// Token account per reward stream mint.
+    stream_atas: BTreeMap<Pubkey, Pubkey>,
+}
+
+pub struct Harness {
+    pub svm: LiteSVM,
+    pub keys: PoolKeys,
+    admin: Wallet,
+    users: BTreeMap<Pubkey, Wallet>,
+    /// Mints of the reward streams added so far, in pool order.
+    streams: Vec<Pubkey>,
+}
+
+impl Harness {
+    /// A pool staking one mint and paying rewards in another, initialized at
+    /// `START` with an open-ended `reward_rate_per_second`.
+    pub fn new(reward_rate_per_second: u64) -> Self {
+        Self::with_mints(reward_rate_per_second, false)
+    }
+
+    /// A pool that stakes and pays rewards in the same mint, as `compound`
+    /// requires.
+    pub fn single_mint(reward_rate_per_second: u64) -> Self {
+        Self::with_mints(reward_rate_per_second, true)
+    }
+
+    fn with_mints(reward_rate_per_second: u64, single_mint: bool) -> Self {
+        let mut svm = LiteSVM::new();
+        let path = program_path();
+        svm.add_program_from_file(ID, &path).unwrap_or_else(|err| {
+            panic!("cannot load {}: {err}; build the program with `anchor build`", path.display())
+        });
+
+        let mut clock: Clock = svm.get_sysvar();
+        clock.unix_timestamp = START;
+        svm.set_sysvar(&clock);
+
+        let admin = Keypair::new();
+        svm.airdrop(&admin.pubkey(), LAMPORTS).unwrap();
+
+        let stake_mint = Pubkey::new_unique();
+        create_mint(&mut svm, &stake_mint, &admin.pubkey());
+        let reward_mint = if single_mint {
+            stake_mint
+        } else {
+            let mint = Pubkey::new_unique();
+            create_mint(&mut svm, &mint, &admin.pubkey());
+            mint
+        };
+
+        let keys = PoolKeys::new(stake_mint, reward_mint);
+        let admin = Wallet {
+            stake_ata: create_token_account(&mut svm, &stake_mint, &admin.pubkey(), 0),
+            reward_ata: create_token_account(&mut svm, &reward_mint, &admin.pubkey(), u64::MAX / 2),
+            stream_atas: BTreeMap::new(),
+            keypair: admin,
+        };
+
+        let mut harness = Self {
+            svm,
+            keys,
+            admin,
+            users: BTreeMap::new(),
+            streams: Vec::new(),
+        };
+        harness.initialize_pool(reward_rate_per_second).unwrap();
+        harness
+    }
+
+    pub fn admin(&self) -> Pubkey {
+        self.admin.keypair.pubkey()
+    }
+
+    /// A funded wallet holding `WALLET_BALANCE` stake tokens, with an empty
+    /// token account for the reward mint and for every stream added so far.
+    pub fn new_user(&mut self) -> Pubkey {
+        let keypair = Keypair::new();
+        let owner = keypair.pubkey();
+        self.svm.airdrop(&owner, LAMPORTS).unwrap();
+
+        let stake_ata =
+            create_token_account(&mut self.svm, &self.keys.stake_mint, &owner, WALLET_BALANCE);
+        let reward_ata = create_token_account(&mut self.svm, &self.keys.reward_mint, &owner, 0);
+        let stream_atas = self
+            .streams
+            .iter()
+            .map(|mint| (*mint, create_token_account(&mut self.svm, mint, &owner, 0)))
+            .collect();
+
+        self.users.insert(
+            owner,
+            Wallet {
+                keypair,
+                stake_ata,
+                reward_ata,
+                stream_atas,
+            },
+        );
+        owner
+    }
+
+    pub fn now(&self) -> i64 {
+        self.svm.get_sysvar::<Clock>().unix_timestamp
+    }
+
+    pub fn warp_to(&mut self, timestamp: i64) {
+        let mut clock: Clock = self.svm.get_sysvar();
+        assert!(timestamp >= clock.unix_timestamp, "the clock only moves forward");
+        clock.unix_timestamp = timestamp;
+        self.svm.set_sysvar(&clock);
+    }
+
+    pub fn warp(&mut self, seconds: i64) {
+        self.warp_to(self.now() + seconds);
+    }
+
+    pub fn pool(&self) -> Pool {
+        let account = self.svm.get_account(&self.keys.pool).expect("pool is closed");
+        decode_pool(&account.data).unwrap()
+    }
+
+    pub fn pool_exists(&self) -> bool {
+        self.svm.get_account(&self.keys.pool).is_some_and(|account| account.lamports > 0)
+    }
+
+    /// The owner's position, if it is open.
+    pub fn position(&self, owner: &Pubkey) -> Option<UserStake> {
+        let account = self.svm.get_account(&self.keys.user_stake(owner))?;
+        (account.lamports > 0).then(|| decode_user_stake(&account.data).unwrap())
+    }
+
+    pub fn user(&self, owner: &Pubkey) -> UserStake {
+        self.position(owner).expect("position is not open")
+    }
+
+    /// Every open position, by owner.
+    pub fn positions(&self) -> Vec<(Pubkey, UserStake)> {
+        self.users
+            .keys()
+            .filter_map(|owner| Some((*owner, self.position(owner)?)))
+            .collect()
+    }
+
+    pub fn token_balance(&self, token_account: &Pubkey) -> u64 {
+        self.svm
+            .get_account(token_account)
+            .filter(|account| account.lamports > 0)
+            .map_or(0, |account| spl_token::state::Account::unpack(&account.data).unwrap().amount)
+    }
+
+    pub fn stake_vault(&self) -> u64 {
+        self.token_balance(&self.keys.stake_vault)
+    }
+
+    pub fn reward_vault(&self) -> u64 {
+        self.token_balance(&self.keys.reward_vault)
+    }
+
+    pub fn lamports(&self, owner: &Pubkey) -> u64 {
+        self.svm.get_balance(owner).unwrap_or(0)
+    }
+
+    /// Stake tokens left in the owner's wallet.
+    pub fn wallet(&self, owner: &Pubkey) -> u64 {
+        self.token_balance(&self.users[owner].stake_ata)
+    }
+
+    /// Primary reward tokens paid out to the owner so far.
+    pub fn paid(&self, owner: &Pubkey) -> u64 {
+        self.token_balance(&self.users[owner].reward_ata)
+    }
+
+    pub fn stream_vault(&self, mint: &Pubkey) -> u64 {
+        self.token_balance(&self.keys.stream_vault(mint))
+    }
+
+    /// Rewards of stream `mint` paid out to the owner so far.
+    pub fn stream_paid(&self, owner: &Pubkey, mint: &Pubkey) -> u64 {
+        self.token_balance(&self.users[owner].stream_atas[mint])
+    }
+
+    /// Reward tokens the admin holds outside the pool.
+    pub fn admin_rewards(&self) -> u64 {
+        self.token_balance(&self.admin.reward_ata)
+    }
+
+    /// Tokens of stream `mint` the admin holds outside the pool.
+    pub fn admin_stream_rewards(&self, mint: &Pubkey) -> u64 {
+        self.token_balance(&self.admin.stream_atas[mint])
+    }
+
+    /// Signs `ix` with `signer`, who also pays the fee. Failures other than
+    /// a program's custom error abort the test with the transaction logs.
+    fn send(svm: &mut LiteSVM, ix: Instruction, signer: &Keypair) -> Result<()> {
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&signer.pubkey()),
+            &[signer],
+            svm.latest_blockhash(),
+        );
+        let result = svm.send_transaction(tx);
+        // Lets an identical transaction be sent again
+        svm.expire_blockhash();
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(failed) => match failed.err {
+                TransactionError::InstructionError(_, InstructionError::Custom(code)) => Err(code),
+                err => panic!("transaction failed: {err}\n{}", failed.meta.pretty_logs()),
+            },
+        }
+    }
+
+    fn send_as_admin(&mut self, ix: Instruction) -> Result<()> {
+        Self::send(&mut self.svm, ix, &self.admin.keypair)
+    }
+
+    fn send_as(&mut self, owner: &Pubkey, ix: Instruction) -> Result<()> {
+        Self::send(&mut self.svm, ix, &self.users[owner].keypair)
+    }
+
+    /// Sends `ix` as `owner` and returns how much their stake and reward
+    /// token accounts grew.
+    fn send_as_measured(&mut self, owner: &Pubkey, ix: Instruction) -> Result<(u64, u64)> {
+        let stake_before = self.wallet(owner);
+        let rewards_before = self.paid(owner);
+        self.send_as(owner, ix)?;
+        Ok((self.wallet(owner) - stake_before, self.paid(owner) - rewards_before))
+    }
+
+    pub fn initialize_pool(&mut self, reward_rate_per_second: u64) -> Result<()> {
+        let ix = instructions::initialize_pool(
+            &self.keys,
+            &self.admin(),
+            &spl_token::ID,
+            &spl_token::ID,
+            reward_rate_per_second,
+        );
+        self.send_as_admin(ix)
+    }
+
+    pub fn deposit_rewards(&mut self, amount: u64, duration: u64) -> Result<()> {
+        let ix = instructions::deposit_rewards(
+            &self.keys,
+            &self.admin(),
+            &self.admin.reward_ata,
+            &spl_token::ID,
+            amount,
+            duration,
+        );
+        self.send_as_admin(ix)
+    }
+
+    pub fn set_reward_rate(&mut self, new_rate: u64) -> Result<()> {
+        let ix = instructions::set_reward_rate(&self.keys, &self.admin(), new_rate);
+        self.send_as_admin(ix)
+    }
+
+    pub fn stake(&mut self, owner: &Pubkey, amount: u64) -> Result<()> {
+        let ix = instructions::stake(
+            &self.keys,
+            owner,
+            &self.users[owner].stake_ata,
+            &spl_token::ID,
+            amount,
+        );
+        self.send_as(owner, ix)
+    }
+
+    /// Stakes `funder`'s tokens into `beneficiary`'s position; `funder`
+    /// signs and pays for it.
+    pub fn stake_for(&mut self, funder: &Pubkey, beneficiary: &Pubkey, amount: u64) -> Result<()> {
+        let ix = instructions::stake_for(
+            &self.keys,
+            funder,
+            &self.users[funder].stake_ata,
+            &spl_token::ID,
+            beneficiary,
+            amount,
+        );
+        self.send_as(funder, ix)
+    }
+
+    pub fn unstake(&mut self, owner: &Pubkey, amount: u64) -> Result<()> {
+        let ix = instructions::unstake(
+            &self.keys,
+            owner,
+            &self.users[owner].stake_ata,
+            &spl_token::ID,
+            amount,
+            false,
+        );
+        self.send_as(owner, ix)
+    }
+
+    pub fn set_lockup_tiers(&mut self, tiers: Vec<LockupTier>) -> Result<()> {
+        let ix = instructions::set_lockup_tiers(&self.keys, &self.admin(), tiers);
+        self.send_as_admin(ix)
+    }
+
+    pub fn lock_stake(&mut self, owner: &Pubkey, amount: u64, tier: u8) -> Result<()> {
+        let ix = instructions::lock_stake(&self.keys, owner, amount, tier);
+        self.send_as(owner, ix)
+    }
+
+    pub fn set_cooldown(&mut self, cooldown_seconds: u64) -> Result<()> {
+        let ix = instructions::set_cooldown(&self.keys, &self.admin(), cooldown_seconds);
+        self.send_as_admin(ix)
+    }
+
+    pub fn request_unstake(&mut self, owner: &Pubkey, amount: u64) -> Result<()> {
+        let ix = instructions::request_unstake(&self.keys, owner, amount);
+        self.send_as(owner, ix)
+    }
+
+    /// Withdraws matured unbonding entries and returns the amount paid.
+    pub fn withdraw_unbonded(&mut self, owner: &Pubkey) -> Result<u64> {
+        let ix = instructions::withdraw_unbonded(
+            &self.keys,
+            owner,
+            &self.users[owner].stake_ata,
+            &spl_token::ID,
+        );
+        Ok(self.send_as_measured(owner, ix)?.0)
+    }
+
+    /// Emergency-unstakes and returns the principal paid out right away.
+    pub fn emergency_unstake(&mut self, owner: &Pubkey) -> Result<u64> {
+        let ix = instructions::emergency_unstake(
+            &self.keys,
+            owner,
+            &self.users[owner].stake_ata,
+            &spl_token::ID,
+        );
+        Ok(self.send_as_measured(owner, ix)?.0)
+    }
+
+    /// Simulates `view_pending_rewards` for `owner` and decodes its return
+    /// data.
+    pub fn view_pending_rewards(&self, owner: &Pubkey) -> Result<PendingRewards> {
+        let signer = &self.users[owner].keypair;
+        let tx = Transaction::new_signed_with_payer(
+            &[instructions::view_pending_rewards(&self.keys, owner)],
+            Some(owner),
+            &[signer],
+            self.svm.latest_blockhash(),
+        );
+
+        match self.svm.simulate_transaction(tx) {
+            Ok(info) => Ok(PendingRewards::try_from_slice(&info.meta.return_data.data).unwrap()),
+            Err(failed) => match failed.err {
+                TransactionError::InstructionError(_, InstructionError::Custom(code)) => Err(code),
+                err => panic!("simulation failed: {err}\n{}", failed.meta.pretty_logs()),
+            },
+        }
+    }
+
+    /// Claims primary rewards and returns the amount paid.
+    pub fn claim(&mut self, owner: &Pubkey) -> Result<u64> {
+        let ix = instructions::claim_rewards(
+            &self.keys,
+            owner,
+            &self.users[owner].reward_ata,
+            &spl_token::ID,
+            false,
+        );
+        Ok(self.send_as_measured(owner, ix)?.1)
+    }
+
+    /// Restakes pending rewards in a `single_mint` pool and returns the
+    /// amount taken from the reward vault.
+    pub fn compound(&mut self, owner: &Pubkey) -> Result<u64> {
+        let ix = instructions::compound(&self.keys, owner, &spl_token::ID);
+        let before = self.reward_vault();
+        self.send_as(owner, ix)?;
+        Ok(before - self.reward_vault())
+    }
+
+    /// Exits the pool and returns the principal and primary rewards paid.
+    /// Streams are paid too.
+    pub fn exit(&mut self, owner: &Pubkey) -> Result<(u64, u64)> {
+        let ix = self.exit_ix(owner, false);
+        self.send_as_measured(owner, ix)
+    }
+
+    /// Exits the pool asking for the position to be closed if it is left
+    /// empty.
+    pub fn exit_and_close(&mut self, owner: &Pubkey) -> Result<(u64, u64)> {
+        let ix = self.exit_ix(owner, true);
+        self.send_as_measured(owner, ix)
+    }
+
+    fn exit_ix(&self, owner: &Pubkey, close: bool) -> Instruction {
+        let wallet = &self.users[owner];
+        instructions::exit(
+            &self.keys,
+            owner,
+            &wallet.stake_ata,
+            &wallet.reward_ata,
+            &spl_token::ID,
+            &spl_token::ID,
+            &self.stream_claims(wallet),
+            close,
+        )
+    }
+
+    pub fn close_user_stake(&mut self, owner: &Pubkey) -> Result<()> {
+        let ix = instructions::close_user_stake(&self.keys, owner);
+        self.send_as(owner, ix)
+    }
+
+    pub fn withdraw_excess_rewards(&mut self, amount: u64) -> Result<()> {
+        let ix = instructions::withdraw_excess_rewards(
+            &self.keys,
+            &self.admin(),
+            &self.admin.reward_ata,
+            &spl_token::ID,
+            amount,
+        );
+        self.send_as_admin(ix)
+    }
+
+    /// Adds a reward stream in a new mint and returns the mint. The admin
+    /// and every wallet get a token account for it.
+    pub fn add_reward_stream(&mut self, reward_rate_per_second: u64) -> Result<Pubkey> {
+        let admin = self.admin();
+        let mint = Pubkey::new_unique();
+        create_mint(&mut self.svm, &mint, &admin);
+        let ix = instructions::add_reward_stream(
+            &self.keys,
+            &admin,
+            &mint,
+            &spl_token::ID,
+            reward_rate_per_second,
+        );
+        self.send_as_admin(ix)?;
+
+        let ata = create_token_account(&mut self.svm, &mint, &admin, u64::MAX / 2);
+        self.admin.stream_atas.insert(mint, ata);
+        for (owner, wallet) in self.users.iter_mut() {
+            let ata = create_token_account(&mut self.svm, &mint, owner, 0);
+            wallet.stream_atas.insert(mint, ata);
+        }
+        self.streams.push(mint);
+        Ok(mint)
+    }
+
+    pub fn deposit_stream_rewards(&mut self, mint: &Pubkey, amount: u64, duration: u64) -> Result<()> {
+        let ix = instructions::deposit_stream_rewards(
+            &self.keys,
+            &self.admin(),
+            mint,
+            &self.admin.stream_atas[mint],
+            &spl_token::ID,
+            amount,
+            duration,
+        );
+        self.send_as_admin(ix)
+    }
+
+    /// One `StreamClaim` per stream, paying into `wallet`'s token accounts.
+    fn stream_claims(&self, wallet: &Wallet) -> Vec<StreamClaim> {
+        self.streams
+            .iter()
+            .map(|mint| StreamClaim {
+                mint: *mint,
+                user_reward_ata: wallet.stream_atas[mint],
+                token_program: spl_token::ID,
+            })
+            .collect()
+    }
+
+    /// Claims primary and stream rewards in one call.
+    pub fn claim_all(&mut self, owner: &Pubkey) -> Result<()> {
+        let wallet = &self.users[owner];
+        let ix = instructions::claim_all_rewards(
+            &self.keys,
+            owner,
+            &wallet.reward_ata,
+            &spl_token::ID,
+            &self.stream_claims(wallet),
+        );
+        self.send_as(owner, ix)
+    }
+
+    /// Closes the pool and returns the primary reward tokens swept to the
+    /// admin.
+    pub fn close_pool(&mut self) -> Result<u64> {
+        let streams = self.stream_claims(&self.admin);
+        let ix = instructions::close_pool(
+            &self.keys,
+            &self.admin(),
+            &self.admin.stake_ata,
+            &self.admin.reward_ata,
+            &spl_token::ID,
+            &spl_token::ID,
+            &streams,
+        );
+
+        let before = self.admin_rewards();
+        self.send_as_admin(ix)?;
+        self.streams.clear();
+        Ok(self.admin_rewards() - before)
+    }
+}
+
+fn create_mint(svm: &mut LiteSVM, mint: &Pubkey, authority: &Pubkey) {
+    let state = spl_token::state::Mint {
+        mint_authority: COption::Some(*authority),
+        supply: 0,
+        decimals: DECIMALS,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    state.pack_into_slice(&mut data);
+    set_token_program_account(svm, mint, data);
+}
+
+/// Creates a token account for `mint` owned by `owner` holding `amount`.
+fn create_token_account(svm: &mut LiteSVM, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Pubkey {
+    let address = Pubkey::new_unique();
+    let state = spl_token::state::Account {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    };
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    state.pack_into_slice(&mut data);
+    set_token_program_account(svm, &address, data);
+    address
+}
+
+fn set_token_program_account(svm: &mut LiteSVM, address: &Pubkey, data: Vec<u8>) {
+    let account = Account {
+        lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    svm.set_account(*address, account).unwrap();
+}
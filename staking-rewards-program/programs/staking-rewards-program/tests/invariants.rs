@@ -0,0 +1,291 @@
+//! Property tests: random sequences of stakes, locks, unstakes (instant,
+//! unbonding and emergency), claims, compounding, exits, deposits, rate
+//! changes, excess-reward withdrawals and time warps across several users, on
+//! a single-mint pool with a reward stream, checking the pool's global
+//! invariants after every step. Runs the compiled program, so it is ignored
+//! by default; see `tests/common`.
+
+mod common;
+
+use anchor_lang::prelude::*;
+use common::Harness;
+use proptest::prelude::*;
+use staking_rewards_program::math::{self, last_time_reward_applicable};
+use staking_rewards_program::{pending_rewards, LockupTier, StakingError};
+
+const USERS: usize = 4;
+
+const TIERS: [LockupTier; 2] = [
+    LockupTier {
+        duration: 500,
+        multiplier_bps: 15_000,
+    },
+    LockupTier {
+        duration: 2_000,
+        multiplier_bps: 30_000,
+    },
+];
+
+#[derive(Clone, Copy, Debug)]
+enum Action {
+    Stake { user: usize, amount: u64 },
+    Unstake { user: usize, amount: u64 },
+    LockStake { user: usize, amount: u64, tier: u8 },
+    RequestUnstake { user: usize, amount: u64 },
+    WithdrawUnbonded { user: usize },
+    EmergencyUnstake { user: usize },
+    Claim { user: usize },
+    ClaimAll { user: usize },
+    Compound { user: usize },
+    Exit { user: usize },
+    Deposit { amount: u64, duration: u64 },
+    DepositStream { amount: u64, duration: u64 },
+    SetRewardRate { rate: u64 },
+    WithdrawExcess { amount: u64 },
+    Warp { seconds: i64 },
+}
+
+fn action() -> impl Strategy<Value = Action> {
+    prop_oneof![
+        (0..USERS, 1..1_000_000u64).prop_map(|(user, amount)| Action::Stake { user, amount }),
+        (0..USERS, 1..1_000_000u64).prop_map(|(user, amount)| Action::Unstake { user, amount }),
+        (0..USERS, 1..1_000_000u64, 0..TIERS.len() as u8)
+            .prop_map(|(user, amount, tier)| Action::LockStake { user, amount, tier }),
+        (0..USERS, 1..1_000_000u64)
+            .prop_map(|(user, amount)| Action::RequestUnstake { user, amount }),
+        (0..USERS).prop_map(|user| Action::WithdrawUnbonded { user }),
+        (0..USERS).prop_map(|user| Action::EmergencyUnstake { user }),
+        (0..USERS).prop_map(|user| Action::Claim { user }),
+        (0..USERS).prop_map(|user| Action::ClaimAll { user }),
+        (0..USERS).prop_map(|user| Action::Compound { user }),
+        (0..USERS).prop_map(|user| Action::Exit { user }),
+        (0..10_000_000u64, 0..1_000u64)
+            .prop_map(|(amount, duration)| Action::Deposit { amount, duration }),
+        (0..10_000_000u64, 0..1_000u64)
+            .prop_map(|(amount, duration)| Action::DepositStream { amount, duration }),
+        (0..1_000u64).prop_map(|rate| Action::SetRewardRate { rate }),
+        (1..10_000_000u64).prop_map(|amount| Action::WithdrawExcess { amount }),
+        (0..10_000i64).prop_map(|seconds| Action::Warp { seconds }),
+    ]
+}
+
+/// Rewards a schedule released between `from` and `to` while the pool had
+/// stakers, computed independently of the accumulator.
+fn emitted_between(rate: u64, period_finish: i64, total_staked: u128, from: i64, to: i64) -> u128 {
+    if total_staked == 0 {
+        return 0;
+    }
+    let elapsed = last_time_reward_applicable(period_finish, to)
+        - last_time_reward_applicable(period_finish, from);
+
+    rate as u128 * elapsed.max(0) as u128
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    #[ignore = "needs `anchor build`"]
+    fn reward_accounting_invariants(
+        rate in 0..1_000u64,
+        stream_rate in 0..1_000u64,
+        cooldown in prop_oneof![Just(0u64), 1..1_000u64],
+        initial_rewards in 0..10_000_000u64,
+        initial_stream_rewards in 0..10_000_000u64,
+        actions in prop::collection::vec(action(), 1..64),
+    ) {
+        let mut h = Harness::single_mint(rate);
+        h.deposit_rewards(initial_rewards, 0).unwrap();
+        h.set_lockup_tiers(TIERS.to_vec()).unwrap();
+        h.set_cooldown(cooldown).unwrap();
+        let stream = h.add_reward_stream(stream_rate).unwrap();
+        h.deposit_stream_rewards(&stream, initial_stream_rewards, 0).unwrap();
+        let users: Vec<Pubkey> = (0..USERS).map(|_| h.new_user()).collect();
+
+        let mut funded = initial_rewards as u128;
+        let mut emitted = 0u128;
+        let mut compounded = 0u128;
+        let mut forfeited = 0u128;
+        let mut stream_funded = initial_stream_rewards as u128;
+        let mut stream_emitted = 0u128;
+        let mut stream_forfeited = 0u128;
+        let mut settlements = 0u128;
+        let mut last = h.now();
+
+        for action in actions {
+            if let Action::Warp { seconds } = action {
+                h.warp(seconds);
+                continue;
+            }
+
+            // The schedule in force since the last step is the one read
+            // before this step changes it
+            let now = h.now();
+            let before = h.pool();
+            emitted += emitted_between(
+                before.reward_rate_per_second,
+                before.period_finish,
+                before.total_staked,
+                last,
+                now,
+            );
+            let schedule = &before.reward_streams[0];
+            stream_emitted += emitted_between(
+                schedule.reward_rate_per_second,
+                schedule.period_finish,
+                before.total_staked,
+                last,
+                now,
+            );
+            last = now;
+
+            // Clamp amounts to what the position can actually move, so most
+            // of these actions succeed
+            let unlocked = |h: &Harness, user: usize| {
+                h.position(&users[user]).map_or(0, |u| u.unlocked_amount(now) as u64)
+            };
+
+            let result = match action {
+                Action::Stake { user, amount } => h.stake(&users[user], amount),
+                Action::Unstake { user, amount } => {
+                    let amount = amount.min(unlocked(&h, user)).max(1);
+                    h.unstake(&users[user], amount)
+                }
+                Action::LockStake { user, amount, tier } => {
+                    let amount = amount.min(unlocked(&h, user)).max(1);
+                    h.lock_stake(&users[user], amount, tier)
+                }
+                Action::RequestUnstake { user, amount } => {
+                    let amount = amount.min(unlocked(&h, user)).max(1);
+                    h.request_unstake(&users[user], amount)
+                }
+                Action::WithdrawUnbonded { user } => h.withdraw_unbonded(&users[user]).map(|_| ()),
+                Action::EmergencyUnstake { user } => {
+                    // Whatever the position had earned is given up
+                    let owed = h
+                        .position(&users[user])
+                        .map(|u| pending_rewards(&before, &u, now).unwrap());
+                    let result = h.emergency_unstake(&users[user]).map(|_| ());
+                    if let (Ok(()), Some(owed)) = (&result, owed) {
+                        forfeited += owed.pending;
+                        stream_forfeited += owed.streams[0].pending;
+                    }
+                    result
+                }
+                Action::Claim { user } => h.claim(&users[user]).map(|_| ()),
+                Action::ClaimAll { user } => h.claim_all(&users[user]),
+                Action::Compound { user } => h.compound(&users[user]).map(|amount| {
+                    compounded += amount as u128;
+                }),
+                Action::Exit { user } => h.exit(&users[user]).map(|_| ()),
+                Action::Deposit { amount, duration } => {
+                    let result = h.deposit_rewards(amount, duration);
+                    if result.is_ok() {
+                        funded += amount as u128;
+                    }
+                    result
+                }
+                Action::DepositStream { amount, duration } => {
+                    let result = h.deposit_stream_rewards(&stream, amount, duration);
+                    if result.is_ok() {
+                        stream_funded += amount as u128;
+                    }
+                    result
+                }
+                Action::SetRewardRate { rate } => h.set_reward_rate(rate),
+                Action::WithdrawExcess { amount } => {
+                    let result = h.withdraw_excess_rewards(amount);
+                    if result.is_ok() {
+                        funded -= amount as u128;
+                    }
+                    result
+                }
+                Action::Warp { .. } => unreachable!(),
+            };
+
+            let succeeded = result.is_ok();
+            match result {
+                Ok(()) => settlements += 1,
+                Err(err) => prop_assert_ne!(err, u32::from(StakingError::Overflow)),
+            }
+
+            let pool = h.pool();
+            let positions = h.positions();
+
+            // Principal: positions, pool totals and vault agree, whether
+            // staked or waiting in an unbonding entry
+            let staked: u128 = positions.iter().map(|(_, u)| u.amount_staked).sum();
+            let weighted: u128 = positions.iter().map(|(_, u)| u.effective_stake).sum();
+            let unbonding: u128 = positions
+                .iter()
+                .flat_map(|(_, u)| u.unbonding.iter())
+                .map(|entry| entry.amount as u128)
+                .sum();
+            prop_assert_eq!(staked + unbonding, h.stake_vault() as u128);
+            prop_assert_eq!(unbonding, pool.total_unbonding);
+            prop_assert_eq!(weighted, pool.total_staked);
+
+            // Rewards: everything paid, compounded, forfeited or owed was
+            // emitted, and only rounding dust was lost along the way
+            let paid: u128 = users.iter().map(|user| h.paid(user) as u128).sum();
+            let mut owed = 0u128;
+            let mut stream_owed = 0u128;
+            for (_, user) in &positions {
+                let pending = pending_rewards(&pool, user, now).unwrap();
+                owed += pending.pending;
+                stream_owed += pending.streams[0].pending;
+            }
+            let accounted = paid + compounded + forfeited + owed;
+            prop_assert!(
+                accounted <= emitted,
+                "paid {} + compounded {} + forfeited {} + owed {} > emitted {}",
+                paid, compounded, forfeited, owed, emitted
+            );
+            prop_assert!(emitted - accounted <= settlements + USERS as u128);
+            // What positions hold as pending is tracked exactly
+            let settled: u128 = positions.iter().map(|(_, u)| u.pending_rewards).sum();
+            prop_assert_eq!(pool.rewards_owed, settled);
+            // The pool's unpaid counter lags by whatever has not been
+            // checkpointed yet
+            let unchecked = math::emitted(
+                pool.reward_rate_per_second,
+                pool.last_update_time,
+                pool.period_finish,
+                pool.total_staked,
+                now,
+            )
+            .unwrap();
+            prop_assert_eq!(
+                pool.rewards_unpaid + unchecked,
+                emitted - paid - compounded - forfeited
+            );
+
+            // Withdrawing excess never leaves accrued rewards unbacked
+            if matches!(action, Action::WithdrawExcess { .. }) && succeeded {
+                prop_assert!(h.reward_vault() as u128 >= owed);
+            }
+
+            // Reward vault: funded = paid + compounded + still held
+            prop_assert_eq!(paid + compounded + h.reward_vault() as u128, funded);
+
+            // The stream follows the same rules, without an unpaid counter
+            let stream_paid: u128 = users
+                .iter()
+                .map(|user| h.stream_paid(user, &stream) as u128)
+                .sum();
+            let stream_accounted = stream_paid + stream_forfeited + stream_owed;
+            prop_assert!(
+                stream_accounted <= stream_emitted,
+                "stream paid {} + forfeited {} + owed {} > emitted {}",
+                stream_paid, stream_forfeited, stream_owed, stream_emitted
+            );
+            prop_assert!(stream_emitted - stream_accounted <= settlements + USERS as u128);
+            let stream_settled: u128 = positions
+                .iter()
+                .map(|(_, u)| u.stream_rewards[0].pending_rewards)
+                .sum();
+            prop_assert_eq!(pool.reward_streams[0].rewards_owed, stream_settled);
+            prop_assert_eq!(stream_paid + h.stream_vault(&stream) as u128, stream_funded);
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use staking_rewards_program::math::{
+    claimable, earned, period_rate, reward_per_token, REWARD_PRECISION,
+};
+
+#[test]
+fn accrues_linearly_and_stops_at_period_finish() {
+    let at = |now| reward_per_token(0, 10, 100, 150, 5, now).unwrap();
+
+    assert_eq!(at(100), 0);
+    assert_eq!(at(120), 20 * 10 * REWARD_PRECISION / 5);
+    assert_eq!(at(150), at(10_000));
+}
+
+#[test]
+fn empty_pool_accrues_nothing() {
+    assert_eq!(reward_per_token(7, 1_000, 0, 0, 0, 1_000), Some(7));
+}
+
+#[test]
+fn rounds_down_in_the_pools_favour() {
+    // 10 tokens over 3 staked: 3.33.. per token
+    let per_token = reward_per_token(0, 10, 0, 0, 3, 1).unwrap();
+    assert_eq!(per_token, 3_333_333_333_333);
+    assert_eq!(earned(1, 0, 0, per_token), Some(3));
+
+    // 1_000 over 300s leaves a 100 remainder undistributed rather than owed
+    assert_eq!(period_rate(0, 0, 1_000, 300, 0), Some(3));
+}
+
+#[test]
+fn period_rate_rolls_over_what_is_left() {
+    // 40s left at 5/s plus 800 new, over 100s
+    assert_eq!(period_rate(5, 140, 800, 100, 100), Some(10));
+    assert_eq!(period_rate(5, 140, 800, 100, 200), Some(8));
+}
+
+#[test]
+fn overflow_is_reported_not_wrapped() {
+    assert_eq!(reward_per_token(u128::MAX, 1, 0, 0, 1, 1), None);
+    assert_eq!(earned(u128::MAX, 0, 0, 2), None);
+    assert_eq!(earned(1, 2, 0, 1), None);
+}
+
+#[test]
+fn claim_is_capped_by_the_vault() {
+    assert_eq!(claimable(700, 500), (500, 200));
+    assert_eq!(claimable(300, 500), (300, 0));
+}
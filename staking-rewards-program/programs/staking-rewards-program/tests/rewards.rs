@@ -0,0 +1,602 @@
+//! Reward scenarios run against the compiled program. They need the SBF build,
+//! so they are ignored by default; see `tests/common`.
+
+mod common;
+
+use anchor_lang::prelude::{ErrorCode, Pubkey};
+use common::{Harness, START, WALLET_BALANCE};
+use staking_rewards_program::utils::committed_rewards;
+use staking_rewards_program::{LockupTier, StakingError};
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn sole_staker_earns_the_full_rate() {
+    let mut h = Harness::new(100);
+    h.deposit_rewards(1_000_000, 0).unwrap();
+    let alice = h.new_user();
+
+    h.stake(&alice, 1_000).unwrap();
+    h.warp(60);
+
+    assert_eq!(h.claim(&alice).unwrap(), 6_000);
+    assert_eq!(h.reward_vault(), 1_000_000 - 6_000);
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn rewards_split_by_share_of_stake() {
+    let mut h = Harness::new(100);
+    h.deposit_rewards(1_000_000, 0).unwrap();
+    let alice = h.new_user();
+    let bob = h.new_user();
+
+    h.stake(&alice, 400).unwrap();
+    h.warp(10);
+    h.stake(&bob, 100).unwrap();
+    h.warp(20);
+
+    // 10s alone, then 20s at 4:1
+    assert_eq!(h.claim(&alice).unwrap(), 1_000 + 1_600);
+    assert_eq!(h.claim(&bob).unwrap(), 400);
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn unstaking_stops_accrual() {
+    let mut h = Harness::new(100);
+    h.deposit_rewards(1_000_000, 0).unwrap();
+    let alice = h.new_user();
+    let bob = h.new_user();
+
+    h.stake(&alice, 500).unwrap();
+    h.stake(&bob, 500).unwrap();
+    h.warp(20);
+    h.unstake(&alice, 500).unwrap();
+    h.warp(30);
+
+    assert_eq!(h.claim(&alice).unwrap(), 1_000);
+    assert_eq!(h.claim(&bob).unwrap(), 1_000 + 3_000);
+    assert_eq!(h.pool().total_staked, 500);
+    assert_eq!(h.stake_vault(), 500);
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn partial_unstake_reweights_later_rewards() {
+    let mut h = Harness::new(120);
+    h.deposit_rewards(1_000_000, 0).unwrap();
+    let alice = h.new_user();
+    let bob = h.new_user();
+
+    h.stake(&alice, 200).unwrap();
+    h.stake(&bob, 100).unwrap();
+    h.warp(10);
+    h.unstake(&alice, 100).unwrap();
+    h.warp(10);
+
+    // 2:1 for the first 10s, 1:1 for the next
+    assert_eq!(h.claim(&alice).unwrap(), 800 + 600);
+    assert_eq!(h.claim(&bob).unwrap(), 400 + 600);
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn claiming_twice_pays_only_new_accrual() {
+    let mut h = Harness::new(10);
+    h.deposit_rewards(1_000_000, 0).unwrap();
+    let alice = h.new_user();
+
+    h.stake(&alice, 50).unwrap();
+    h.warp(5);
+    assert_eq!(h.claim(&alice).unwrap(), 50);
+
+    assert_eq!(
+        h.claim(&alice).unwrap_err(),
+        u32::from(StakingError::NoRewardsAccrued)
+    );
+
+    h.warp(7);
+    assert_eq!(h.claim(&alice).unwrap(), 70);
+    assert_eq!(h.paid(&alice), 120);
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn funded_period_stops_at_period_finish() {
+    let mut h = Harness::new(0);
+    let alice = h.new_user();
+
+    h.stake(&alice, 1_000).unwrap();
+    h.warp(5);
+    h.deposit_rewards(10_000, 100).unwrap();
+
+    assert_eq!(h.pool().reward_rate_per_second, 100);
+    assert_eq!(h.pool().period_finish, START + 105);
+
+    h.warp(150);
+    assert_eq!(h.claim(&alice).unwrap(), 10_000);
+    assert_eq!(h.reward_vault(), 0);
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn topping_up_a_running_period_rolls_over_the_remainder() {
+    let mut h = Harness::new(0);
+    let alice = h.new_user();
+
+    h.stake(&alice, 10).unwrap();
+    h.deposit_rewards(1_000, 100).unwrap();
+    h.warp(40);
+    // 600 undistributed + 900 new over the next 100s
+    h.deposit_rewards(900, 100).unwrap();
+
+    assert_eq!(h.pool().reward_rate_per_second, 15);
+    assert_eq!(h.pool().period_finish, START + 140);
+
+    h.warp(100);
+    assert_eq!(h.claim(&alice).unwrap(), 400 + 1_500);
+    assert_eq!(h.reward_vault(), 0);
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn new_rate_applies_once_a_funded_period_has_ended() {
+    let mut h = Harness::new(0);
+    let alice = h.new_user();
+
+    h.stake(&alice, 100).unwrap();
+    h.deposit_rewards(1_000, 100).unwrap();
+    h.warp(150);
+
+    // The pool goes back to open-ended emission from now, not from the finish
+    h.set_reward_rate(10).unwrap();
+    assert_eq!(h.pool().period_finish, 0);
+    assert_eq!(h.pool().last_update_time, START + 150);
+
+    h.deposit_rewards(500, 0).unwrap();
+    h.warp(20);
+    assert_eq!(h.claim(&alice).unwrap(), 1_000 + 200);
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn raising_the_rate_mid_period_must_stay_funded() {
+    let mut h = Harness::new(0);
+    let alice = h.new_user();
+
+    h.stake(&alice, 100).unwrap();
+    h.deposit_rewards(10_000, 100).unwrap();
+    h.warp(40);
+
+    // 4_000 emitted leaves 6_000 for the last 60s: 100/s at most
+    assert_eq!(
+        h.set_reward_rate(101).unwrap_err(),
+        u32::from(StakingError::InsufficientFunds)
+    );
+
+    h.deposit_rewards(1_200, 0).unwrap();
+    h.set_reward_rate(120).unwrap();
+    assert_eq!(h.pool().period_finish, START + 100);
+
+    h.warp(100);
+    assert_eq!(h.claim(&alice).unwrap(), 4_000 + 7_200);
+    assert_eq!(h.reward_vault(), 0);
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn short_vault_pays_what_it_holds_and_keeps_the_rest_owed() {
+    let mut h = Harness::new(100);
+    h.deposit_rewards(500, 0).unwrap();
+    let alice = h.new_user();
+
+    h.stake(&alice, 1).unwrap();
+    h.warp(10);
+
+    assert_eq!(h.claim(&alice).unwrap(), 500);
+    assert_eq!(h.user(&alice).pending_rewards, 500);
+    assert_eq!(
+        h.claim(&alice).unwrap_err(),
+        u32::from(StakingError::RewardVaultDepleted)
+    );
+
+    h.deposit_rewards(2_000, 0).unwrap();
+    assert_eq!(h.claim(&alice).unwrap(), 500);
+    assert_eq!(h.paid(&alice), 1_000);
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn rounding_never_pays_out_more_than_emitted() {
+    let mut h = Harness::new(10);
+    h.deposit_rewards(1_000_000, 0).unwrap();
+    let users: Vec<_> = (0..3).map(|_| h.new_user()).collect();
+
+    for user in &users {
+        h.stake(user, 1).unwrap();
+    }
+    h.warp(1);
+
+    // 10 tokens split three ways: each is owed 3.33.., rounded down
+    for user in &users {
+        assert_eq!(h.claim(user).unwrap(), 3);
+    }
+    assert_eq!(h.reward_vault(), 1_000_000 - 9);
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn checkpointing_every_second_does_not_round_accrual_away() {
+    let mut h = Harness::new(1);
+    h.deposit_rewards(1_000, 0).unwrap();
+    let alice = h.new_user();
+    let bob = h.new_user();
+
+    // 1/s over ~2e12 staked moves the accumulator only every other second
+    h.stake(&alice, WALLET_BALANCE).unwrap();
+    h.stake(&bob, WALLET_BALANCE - 100).unwrap();
+    for _ in 0..10 {
+        h.warp(1);
+        h.stake(&bob, 1).unwrap();
+    }
+
+    assert_eq!(h.pool().last_update_time, START + 10);
+    assert_eq!(h.claim(&alice).unwrap(), 5);
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn rejects_unstaking_more_than_staked() {
+    let mut h = Harness::new(1);
+    let alice = h.new_user();
+
+    h.stake(&alice, 100).unwrap();
+
+    assert_eq!(
+        h.unstake(&alice, 101).unwrap_err(),
+        u32::from(StakingError::InsufficientFunds)
+    );
+    assert_eq!(h.user(&alice).amount_staked, 100);
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn emergency_unstake_keeps_locked_principal_until_the_lock_ends() {
+    let mut h = Harness::new(10);
+    h.deposit_rewards(1_000_000, 0).unwrap();
+    h.set_lockup_tiers(vec![LockupTier {
+        duration: 1_000,
+        multiplier_bps: 20_000,
+    }])
+    .unwrap();
+    let alice = h.new_user();
+
+    h.stake(&alice, 300).unwrap();
+    h.lock_stake(&alice, 200, 0).unwrap();
+    h.warp(10);
+
+    // Collect the boosted rewards, then try to walk away with everything
+    assert_eq!(h.claim(&alice).unwrap(), 100);
+    assert_eq!(h.emergency_unstake(&alice).unwrap(), 100);
+
+    let user = h.user(&alice);
+    assert_eq!(user.amount_staked, 0);
+    assert_eq!(user.unbonding[0].amount, 200);
+    assert_eq!(user.unbonding[0].release_time, START + 1_000);
+    assert_eq!(h.pool().total_staked, 0);
+    assert_eq!(h.pool().total_unbonding, 200);
+
+    h.warp(500);
+    assert_eq!(
+        h.withdraw_unbonded(&alice).unwrap_err(),
+        u32::from(StakingError::NothingToWithdraw)
+    );
+
+    h.warp_to(START + 1_000);
+    assert_eq!(h.withdraw_unbonded(&alice).unwrap(), 200);
+    assert_eq!(h.wallet(&alice), WALLET_BALANCE);
+    assert_eq!(h.pool().total_unbonding, 0);
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn emergency_unstake_with_a_cooldown_holds_locked_principal_for_the_longer_wait() {
+    let mut h = Harness::new(10);
+    h.set_lockup_tiers(vec![LockupTier {
+        duration: 100,
+        multiplier_bps: 10_000,
+    }])
+    .unwrap();
+    h.set_cooldown(500).unwrap();
+    let alice = h.new_user();
+
+    h.stake(&alice, 300).unwrap();
+    h.lock_stake(&alice, 200, 0).unwrap();
+
+    assert_eq!(h.emergency_unstake(&alice).unwrap(), 0);
+    let user = h.user(&alice);
+    assert_eq!(user.unbonding[0].amount, 200);
+    assert_eq!(user.unbonding[0].release_time, START + 500);
+    assert_eq!(user.unbonding[1].amount, 100);
+    assert_eq!(user.unbonding[1].release_time, START + 500);
+
+    h.warp(500);
+    assert_eq!(h.withdraw_unbonded(&alice).unwrap(), 300);
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn withdrawing_excess_leaves_the_running_period_funded() {
+    let mut h = Harness::new(0);
+    let alice = h.new_user();
+
+    h.stake(&alice, 100).unwrap();
+    h.deposit_rewards(10_000, 100).unwrap();
+    h.warp(40);
+
+    // 4_000 emitted and 6_000 still to come: nothing is spare
+    assert_eq!(
+        h.withdraw_excess_rewards(1).unwrap_err(),
+        u32::from(StakingError::RewardsCommitted)
+    );
+
+    h.deposit_rewards(2_500, 0).unwrap();
+    assert_eq!(
+        h.withdraw_excess_rewards(2_501).unwrap_err(),
+        u32::from(StakingError::RewardsCommitted)
+    );
+    h.withdraw_excess_rewards(2_500).unwrap();
+    // The withdrawal checkpointed the pool, so the vault holds exactly what
+    // it has committed
+    assert_eq!(
+        committed_rewards(&h.pool(), h.now()).unwrap(),
+        h.reward_vault() as u128
+    );
+
+    h.warp(100);
+    assert_eq!(h.claim(&alice).unwrap(), 10_000);
+    assert_eq!(h.reward_vault(), 0);
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn withdrawing_excess_never_touches_accrued_rewards() {
+    let mut h = Harness::new(100);
+    h.deposit_rewards(10_000, 0).unwrap();
+    let alice = h.new_user();
+    let bob = h.new_user();
+
+    h.stake(&alice, 300).unwrap();
+    h.stake(&bob, 100).unwrap();
+    h.warp(30);
+
+    // Open-ended emission commits only what has accrued so far
+    assert_eq!(
+        h.withdraw_excess_rewards(7_001).unwrap_err(),
+        u32::from(StakingError::RewardsCommitted)
+    );
+    h.withdraw_excess_rewards(7_000).unwrap();
+    assert_eq!(committed_rewards(&h.pool(), h.now()).unwrap(), 3_000);
+
+    assert_eq!(h.claim(&alice).unwrap(), 2_250);
+    assert_eq!(h.claim(&bob).unwrap(), 750);
+    assert_eq!(h.reward_vault(), 0);
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn closing_requires_an_empty_pool() {
+    let mut h = Harness::new(10);
+    let alice = h.new_user();
+
+    h.stake(&alice, 100).unwrap();
+
+    assert_eq!(h.close_pool().unwrap_err(), u32::from(StakingError::PoolNotEmpty));
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn closing_waits_until_every_reward_is_paid() {
+    let mut h = Harness::new(10);
+    h.deposit_rewards(1_000, 0).unwrap();
+    let alice = h.new_user();
+
+    h.stake(&alice, 100).unwrap();
+    h.warp(30);
+    h.unstake(&alice, 100).unwrap();
+
+    // Nothing is staked, but alice still has 300 pending
+    assert_eq!(
+        h.close_pool().unwrap_err(),
+        u32::from(StakingError::RewardsOutstanding)
+    );
+
+    assert_eq!(h.claim(&alice).unwrap(), 300);
+    assert_eq!(h.close_pool().unwrap(), 700);
+    assert!(!h.pool_exists());
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn closing_sweeps_stream_vaults_once_streams_are_paid() {
+    let mut h = Harness::new(0);
+    let mint = h.add_reward_stream(10).unwrap();
+    h.deposit_stream_rewards(&mint, 1_000, 0).unwrap();
+    let alice = h.new_user();
+
+    h.stake(&alice, 100).unwrap();
+    h.warp(10);
+    h.unstake(&alice, 100).unwrap();
+
+    // Unstaking leaves the 100 earned on the stream pending
+    assert_eq!(
+        h.close_pool().unwrap_err(),
+        u32::from(StakingError::RewardsOutstanding)
+    );
+
+    h.claim_all(&alice).unwrap();
+    assert_eq!(h.stream_paid(&alice, &mint), 100);
+
+    let admin_before = h.admin_stream_rewards(&mint);
+    assert_eq!(h.close_pool().unwrap(), 0);
+    assert_eq!(h.admin_stream_rewards(&mint) - admin_before, 900);
+    assert!(h.svm.get_account(&h.keys.stream_vault(&mint)).is_none());
+    assert!(!h.pool_exists());
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn positions_left_open_restart_in_a_recreated_pool() {
+    let mut h = Harness::new(10);
+    h.deposit_rewards(1_000, 0).unwrap();
+    let alice = h.new_user();
+
+    h.stake(&alice, 100).unwrap();
+    h.warp(10);
+    assert_eq!(h.exit(&alice).unwrap(), (100, 100));
+    assert_eq!(h.close_pool().unwrap(), 900);
+
+    // Alice never closed her empty position, so it still carries
+    // checkpoints from the old pool
+    h.initialize_pool(10).unwrap();
+    h.deposit_rewards(1_000, 0).unwrap();
+    assert!(h.user(&alice).reward_per_token_paid > h.pool().reward_per_token_stored);
+
+    // Reading, claiming and exiting are clean no-ops on it
+    assert_eq!(h.view_pending_rewards(&alice).unwrap().pending, 0);
+    assert_eq!(
+        h.claim(&alice).unwrap_err(),
+        u32::from(StakingError::NoRewardsAccrued)
+    );
+    assert_eq!(h.exit(&alice).unwrap(), (0, 0));
+
+    h.stake(&alice, 100).unwrap();
+    h.warp(5);
+    assert_eq!(h.claim(&alice).unwrap(), 50);
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn only_an_empty_position_can_be_closed() {
+    let mut h = Harness::new(10);
+    h.deposit_rewards(1_000, 0).unwrap();
+    let alice = h.new_user();
+
+    h.stake(&alice, 100).unwrap();
+    h.warp(10);
+    assert_eq!(
+        h.close_user_stake(&alice).unwrap_err(),
+        u32::from(StakingError::PositionNotEmpty)
+    );
+
+    // Fully unstaked, yet 100 in rewards is still pending
+    h.unstake(&alice, 100).unwrap();
+    assert_eq!(
+        h.close_user_stake(&alice).unwrap_err(),
+        u32::from(StakingError::PositionNotEmpty)
+    );
+
+    assert_eq!(h.claim(&alice).unwrap(), 100);
+    h.close_user_stake(&alice).unwrap();
+    assert!(h.position(&alice).is_none());
+
+    // Staking again opens a fresh position that earns from then on
+    h.stake(&alice, 100).unwrap();
+    h.warp(5);
+    assert_eq!(h.claim(&alice).unwrap(), 50);
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn exit_returns_principal_and_pays_rewards() {
+    let mut h = Harness::new(10);
+    h.deposit_rewards(1_000, 0).unwrap();
+    let alice = h.new_user();
+    let bob = h.new_user();
+
+    h.stake(&alice, 300).unwrap();
+    h.stake(&bob, 100).unwrap();
+    h.warp(40);
+
+    assert_eq!(h.exit(&alice).unwrap(), (300, 300));
+    assert!(h.user(&alice).is_empty());
+    assert_eq!(h.pool().total_staked, 100);
+
+    // Nothing left to claim is not an error
+    assert_eq!(h.exit(&alice).unwrap(), (0, 0));
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn exit_pays_streams_and_closes_the_position() {
+    let mut h = Harness::new(10);
+    h.deposit_rewards(1_000, 0).unwrap();
+    let mint = h.add_reward_stream(5).unwrap();
+    h.deposit_stream_rewards(&mint, 1_000, 0).unwrap();
+    let alice = h.new_user();
+
+    h.stake(&alice, 100).unwrap();
+    h.warp(20);
+
+    assert_eq!(h.exit_and_close(&alice).unwrap(), (100, 200));
+    assert_eq!(h.stream_paid(&alice, &mint), 100);
+    assert!(h.position(&alice).is_none());
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn exit_from_a_short_vault_keeps_the_rest_owed() {
+    let mut h = Harness::new(10);
+    h.deposit_rewards(50, 0).unwrap();
+    let alice = h.new_user();
+
+    h.stake(&alice, 100).unwrap();
+    h.warp(10);
+
+    assert_eq!(h.exit(&alice).unwrap(), (100, 50));
+    assert_eq!(h.user(&alice).pending_rewards, 50);
+    assert_eq!(
+        h.close_user_stake(&alice).unwrap_err(),
+        u32::from(StakingError::PositionNotEmpty)
+    );
+}
+
+#[test]
+#[ignore = "needs `anchor build`"]
+fn staking_for_a_beneficiary_credits_their_position() {
+    let mut h = Harness::new(10);
+    h.deposit_rewards(1_000, 0).unwrap();
+    let employer = h.new_user();
+    let employee = h.new_user();
+
+    // The employer signs and pays both the tokens and the position's rent
+    let employee_lamports = h.lamports(&employee);
+    h.stake_for(&employer, &employee, 100).unwrap();
+    assert_eq!(h.wallet(&employer), WALLET_BALANCE - 100);
+    assert_eq!(h.wallet(&employee), WALLET_BALANCE);
+    assert_eq!(h.lamports(&employee), employee_lamports);
+    assert_eq!(h.user(&employee).owner, employee);
+    assert!(h.position(&employer).is_none());
+
+    h.stake(&employee, 100).unwrap();
+    h.warp(10);
+    assert_eq!(h.user(&employee).amount_staked, 200);
+
+    // The position is the employee's alone
+    assert_eq!(
+        h.unstake(&employer, 100).unwrap_err(),
+        u32::from(ErrorCode::AccountNotInitialized)
+    );
+    assert_eq!(h.exit(&employee).unwrap(), (200, 100));
+    assert_eq!(h.paid(&employer), 0);
+
+    // Closing refunds the rent to the employee
+    let before_close = h.lamports(&employee);
+    h.close_user_stake(&employee).unwrap();
+    assert!(h.lamports(&employee) > before_close);
+
+    assert_eq!(
+        h.stake_for(&employer, &Pubkey::default(), 100).unwrap_err(),
+        u32::from(StakingError::InvalidBeneficiary)
+    );
+}
@@ -0,0 +1,12 @@
+use anchor_lang::{AccountDeserialize, Result};
+use staking_rewards_program::{Pool, UserStake};
+
+/// Decodes raw `Pool` account data, discriminator included.
+pub fn decode_pool(mut data: &[u8]) -> Result<Pool> {
+    Pool::try_deserialize(&mut data)
+}
+
+/// Decodes raw `UserStake` account data, discriminator included.
+pub fn decode_user_stake(mut data: &[u8]) -> Result<UserStake> {
+    UserStake::try_deserialize(&mut data)
+}
@@ -0,0 +1,10 @@
+use anchor_lang::Result;
+use staking_rewards_program::instructions::view_pending_rewards::pending_rewards;
+use staking_rewards_program::{PendingRewards, Pool, UserStake};
+
+/// Rewards `user` would have pending if settled at unix time `now`. Runs the
+/// same code as the `view_pending_rewards` instruction, so the result matches
+/// the chain exactly for the same account snapshots and timestamp.
+pub fn estimate_pending_rewards(pool: &Pool, user: &UserStake, now: i64) -> Result<PendingRewards> {
+    pending_rewards(pool, user, now)
+}
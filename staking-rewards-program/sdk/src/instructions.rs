@@ -0,0 +1,591 @@
+//! One builder per program instruction. Builders take the pool's `PoolKeys`
+//! plus whatever cannot be derived (signers, token accounts, the token program
+//! owning the mint involved) and fill in every PDA themselves.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use staking_rewards_program::{accounts, instruction, LockupTier, ID};
+
+use crate::pda::{self, PoolKeys};
+
+/// A reward stream to pay out in `claim_all_rewards` or `exit`, or to sweep
+/// in `close_pool`, where `user_reward_ata` is the admin's.
+#[derive(Clone, Copy, Debug)]
+pub struct StreamClaim {
+    pub mint: Pubkey,
+    pub user_reward_ata: Pubkey,
+    pub token_program: Pubkey,
+}
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+fn event_authority() -> Pubkey {
+    pda::event_authority().0
+}
+
+pub fn initialize_pool(
+    keys: &PoolKeys,
+    admin: &Pubkey,
+    stake_token_program: &Pubkey,
+    reward_token_program: &Pubkey,
+    reward_rate_per_second: u64,
+) -> Instruction {
+    build(
+        accounts::InitializePool {
+            admin: *admin,
+            stake_mint: keys.stake_mint,
+            reward_mint: keys.reward_mint,
+            pool: keys.pool,
+            stake_vault: keys.stake_vault,
+            reward_vault: keys.reward_vault,
+            stake_token_program: *stake_token_program,
+            reward_token_program: *reward_token_program,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: ID,
+        },
+        instruction::InitializePool {
+            reward_rate_per_second,
+        },
+    )
+}
+
+pub fn deposit_rewards(
+    keys: &PoolKeys,
+    admin: &Pubkey,
+    admin_reward_ata: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+    duration: u64,
+) -> Instruction {
+    build(
+        accounts::DepositRewards {
+            admin: *admin,
+            reward_mint: keys.reward_mint,
+            admin_reward_ata: *admin_reward_ata,
+            reward_vault: keys.reward_vault,
+            pool: keys.pool,
+            token_program: *token_program,
+            event_authority: event_authority(),
+            program: ID,
+        },
+        instruction::DepositRewards { amount, duration },
+    )
+}
+
+pub fn stake(
+    keys: &PoolKeys,
+    user: &Pubkey,
+    user_stake_ata: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::Stake {
+            user: *user,
+            pool: keys.pool,
+            user_stake: keys.user_stake(user),
+            stake_mint: keys.stake_mint,
+            user_stake_ata: *user_stake_ata,
+            stake_vault: keys.stake_vault,
+            token_program: *token_program,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: ID,
+        },
+        instruction::Stake { amount },
+    )
+}
+
+/// Stakes `funder`'s tokens into a position owned by `beneficiary`.
+pub fn stake_for(
+    keys: &PoolKeys,
+    funder: &Pubkey,
+    funder_stake_ata: &Pubkey,
+    token_program: &Pubkey,
+    beneficiary: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::StakeFor {
+            funder: *funder,
+            pool: keys.pool,
+            user_stake: keys.user_stake(beneficiary),
+            stake_mint: keys.stake_mint,
+            funder_stake_ata: *funder_stake_ata,
+            stake_vault: keys.stake_vault,
+            token_program: *token_program,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: ID,
+        },
+        instruction::StakeFor {
+            beneficiary: *beneficiary,
+            amount,
+        },
+    )
+}
+
+fn unstake_accounts(
+    keys: &PoolKeys,
+    user: &Pubkey,
+    user_stake_ata: &Pubkey,
+    token_program: &Pubkey,
+) -> accounts::Unstake {
+    accounts::Unstake {
+        user: *user,
+        pool: keys.pool,
+        user_stake: keys.user_stake(user),
+        stake_mint: keys.stake_mint,
+        user_stake_ata: *user_stake_ata,
+        stake_vault: keys.stake_vault,
+        token_program: *token_program,
+        event_authority: event_authority(),
+        program: ID,
+    }
+}
+
+pub fn unstake(
+    keys: &PoolKeys,
+    user: &Pubkey,
+    user_stake_ata: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+    close: bool,
+) -> Instruction {
+    build(
+        unstake_accounts(keys, user, user_stake_ata, token_program),
+        instruction::Unstake { amount, close },
+    )
+}
+
+fn claim_accounts(
+    keys: &PoolKeys,
+    user: &Pubkey,
+    user_reward_ata: &Pubkey,
+    token_program: &Pubkey,
+) -> accounts::ClaimRewards {
+    accounts::ClaimRewards {
+        user: *user,
+        pool: keys.pool,
+        user_stake: keys.user_stake(user),
+        reward_mint: keys.reward_mint,
+        user_reward_ata: *user_reward_ata,
+        reward_vault: keys.reward_vault,
+        token_program: *token_program,
+        event_authority: event_authority(),
+        program: ID,
+    }
+}
+
+pub fn claim_rewards(
+    keys: &PoolKeys,
+    user: &Pubkey,
+    user_reward_ata: &Pubkey,
+    token_program: &Pubkey,
+    close: bool,
+) -> Instruction {
+    build(
+        claim_accounts(keys, user, user_reward_ata, token_program),
+        instruction::ClaimRewards { close },
+    )
+}
+
+pub fn set_reward_rate(keys: &PoolKeys, admin: &Pubkey, new_rate: u64) -> Instruction {
+    build(
+        accounts::SetRewardRate {
+            admin: *admin,
+            pool: keys.pool,
+            reward_vault: keys.reward_vault,
+            event_authority: event_authority(),
+            program: ID,
+        },
+        instruction::SetRewardRate { new_rate },
+    )
+}
+
+pub fn add_reward_stream(
+    keys: &PoolKeys,
+    admin: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    reward_rate_per_second: u64,
+) -> Instruction {
+    build(
+        accounts::AddRewardStream {
+            admin: *admin,
+            pool: keys.pool,
+            reward_mint: *mint,
+            stream_vault: keys.stream_vault(mint),
+            token_program: *token_program,
+            system_program: system_program::ID,
+            event_authority: event_authority(),
+            program: ID,
+        },
+        instruction::AddRewardStream {
+            reward_rate_per_second,
+        },
+    )
+}
+
+pub fn deposit_stream_rewards(
+    keys: &PoolKeys,
+    admin: &Pubkey,
+    mint: &Pubkey,
+    admin_reward_ata: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+    duration: u64,
+) -> Instruction {
+    build(
+        accounts::DepositStreamRewards {
+            admin: *admin,
+            reward_mint: *mint,
+            admin_reward_ata: *admin_reward_ata,
+            stream_vault: keys.stream_vault(mint),
+            pool: keys.pool,
+            token_program: *token_program,
+            event_authority: event_authority(),
+            program: ID,
+        },
+        instruction::DepositStreamRewards { amount, duration },
+    )
+}
+
+pub fn claim_stream_rewards(
+    keys: &PoolKeys,
+    user: &Pubkey,
+    mint: &Pubkey,
+    user_reward_ata: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::ClaimStreamRewards {
+            user: *user,
+            pool: keys.pool,
+            user_stake: keys.user_stake(user),
+            reward_mint: *mint,
+            user_reward_ata: *user_reward_ata,
+            stream_vault: keys.stream_vault(mint),
+            token_program: *token_program,
+            event_authority: event_authority(),
+            program: ID,
+        },
+        instruction::ClaimStreamRewards {},
+    )
+}
+
+/// `streams` must list every active stream, in the order they appear on the
+/// pool.
+pub fn claim_all_rewards(
+    keys: &PoolKeys,
+    user: &Pubkey,
+    user_reward_ata: &Pubkey,
+    token_program: &Pubkey,
+    streams: &[StreamClaim],
+) -> Instruction {
+    let mut ix = build(
+        claim_accounts(keys, user, user_reward_ata, token_program),
+        instruction::ClaimAllRewards {},
+    );
+
+    for stream in streams {
+        ix.accounts.extend([
+            AccountMeta::new(keys.stream_vault(&stream.mint), false),
+            AccountMeta::new(stream.user_reward_ata, false),
+            AccountMeta::new_readonly(stream.mint, false),
+            AccountMeta::new_readonly(stream.token_program, false),
+        ]);
+    }
+
+    ix
+}
+
+pub fn set_lockup_tiers(keys: &PoolKeys, admin: &Pubkey, tiers: Vec<LockupTier>) -> Instruction {
+    build(
+        accounts::SetLockupTiers {
+            admin: *admin,
+            pool: keys.pool,
+            event_authority: event_authority(),
+            program: ID,
+        },
+        instruction::SetLockupTiers { tiers },
+    )
+}
+
+pub fn lock_stake(keys: &PoolKeys, user: &Pubkey, amount: u64, tier: u8) -> Instruction {
+    build(
+        accounts::LockStake {
+            user: *user,
+            pool: keys.pool,
+            user_stake: keys.user_stake(user),
+            event_authority: event_authority(),
+            program: ID,
+        },
+        instruction::LockStake { amount, tier },
+    )
+}
+
+/// Permissionless; `owner` is the position whose lock has run out.
+pub fn expire_lock(keys: &PoolKeys, owner: &Pubkey) -> Instruction {
+    build(
+        accounts::ExpireLock {
+            pool: keys.pool,
+            user_stake: keys.user_stake(owner),
+            event_authority: event_authority(),
+            program: ID,
+        },
+        instruction::ExpireLock {},
+    )
+}
+
+pub fn set_cooldown(keys: &PoolKeys, admin: &Pubkey, cooldown_seconds: u64) -> Instruction {
+    build(
+        accounts::SetCooldown {
+            admin: *admin,
+            pool: keys.pool,
+            event_authority: event_authority(),
+            program: ID,
+        },
+        instruction::SetCooldown { cooldown_seconds },
+    )
+}
+
+pub fn request_unstake(keys: &PoolKeys, user: &Pubkey, amount: u64) -> Instruction {
+    build(
+        accounts::RequestUnstake {
+            user: *user,
+            pool: keys.pool,
+            user_stake: keys.user_stake(user),
+            event_authority: event_authority(),
+            program: ID,
+        },
+        instruction::RequestUnstake { amount },
+    )
+}
+
+pub fn withdraw_unbonded(
+    keys: &PoolKeys,
+    user: &Pubkey,
+    user_stake_ata: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        unstake_accounts(keys, user, user_stake_ata, token_program),
+        instruction::WithdrawUnbonded {},
+    )
+}
+
+pub fn emergency_unstake(
+    keys: &PoolKeys,
+    user: &Pubkey,
+    user_stake_ata: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        unstake_accounts(keys, user, user_stake_ata, token_program),
+        instruction::EmergencyUnstake {},
+    )
+}
+
+fn set_paused_accounts(keys: &PoolKeys, admin: &Pubkey) -> accounts::SetPaused {
+    accounts::SetPaused {
+        admin: *admin,
+        pool: keys.pool,
+        event_authority: event_authority(),
+        program: ID,
+    }
+}
+
+pub fn pause(keys: &PoolKeys, admin: &Pubkey, flags: u8) -> Instruction {
+    build(
+        set_paused_accounts(keys, admin),
+        instruction::Pause { flags },
+    )
+}
+
+pub fn unpause(keys: &PoolKeys, admin: &Pubkey, flags: u8) -> Instruction {
+    build(
+        set_paused_accounts(keys, admin),
+        instruction::Unpause { flags },
+    )
+}
+
+pub fn propose_admin(keys: &PoolKeys, admin: &Pubkey, new_admin: &Pubkey) -> Instruction {
+    build(
+        accounts::ProposeAdmin {
+            admin: *admin,
+            pool: keys.pool,
+            event_authority: event_authority(),
+            program: ID,
+        },
+        instruction::ProposeAdmin {
+            new_admin: *new_admin,
+        },
+    )
+}
+
+pub fn accept_admin(keys: &PoolKeys, new_admin: &Pubkey) -> Instruction {
+    build(
+        accounts::AcceptAdmin {
+            new_admin: *new_admin,
+            pool: keys.pool,
+            event_authority: event_authority(),
+            program: ID,
+        },
+        instruction::AcceptAdmin {},
+    )
+}
+
+pub fn compound(keys: &PoolKeys, user: &Pubkey, token_program: &Pubkey) -> Instruction {
+    build(
+        accounts::Compound {
+            user: *user,
+            pool: keys.pool,
+            user_stake: keys.user_stake(user),
+            stake_mint: keys.stake_mint,
+            stake_vault: keys.stake_vault,
+            reward_vault: keys.reward_vault,
+            token_program: *token_program,
+            event_authority: event_authority(),
+            program: ID,
+        },
+        instruction::Compound {},
+    )
+}
+
+/// Meant for simulation; the result comes back as `PendingRewards` return data.
+pub fn view_pending_rewards(keys: &PoolKeys, owner: &Pubkey) -> Instruction {
+    build(
+        accounts::ViewPendingRewards {
+            pool: keys.pool,
+            user_stake: keys.user_stake(owner),
+        },
+        instruction::ViewPendingRewards {},
+    )
+}
+
+pub fn withdraw_excess_rewards(
+    keys: &PoolKeys,
+    admin: &Pubkey,
+    admin_reward_ata: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::WithdrawExcessRewards {
+            admin: *admin,
+            pool: keys.pool,
+            reward_mint: keys.reward_mint,
+            admin_reward_ata: *admin_reward_ata,
+            reward_vault: keys.reward_vault,
+            token_program: *token_program,
+            event_authority: event_authority(),
+            program: ID,
+        },
+        instruction::WithdrawExcessRewards { amount },
+    )
+}
+
+/// Sweeps and closes every vault, streams included, then closes the pool.
+pub fn close_pool(
+    keys: &PoolKeys,
+    admin: &Pubkey,
+    admin_stake_ata: &Pubkey,
+    admin_reward_ata: &Pubkey,
+    stake_token_program: &Pubkey,
+    reward_token_program: &Pubkey,
+    streams: &[StreamClaim],
+) -> Instruction {
+    let mut ix = build(
+        accounts::ClosePool {
+            admin: *admin,
+            pool: keys.pool,
+            stake_mint: keys.stake_mint,
+            reward_mint: keys.reward_mint,
+            admin_stake_ata: *admin_stake_ata,
+            admin_reward_ata: *admin_reward_ata,
+            stake_vault: keys.stake_vault,
+            reward_vault: keys.reward_vault,
+            stake_token_program: *stake_token_program,
+            reward_token_program: *reward_token_program,
+            event_authority: event_authority(),
+            program: ID,
+        },
+        instruction::ClosePool {},
+    );
+
+    for stream in streams {
+        ix.accounts.extend([
+            AccountMeta::new(keys.stream_vault(&stream.mint), false),
+            AccountMeta::new(stream.user_reward_ata, false),
+            AccountMeta::new(stream.mint, false),
+            AccountMeta::new_readonly(stream.token_program, false),
+        ]);
+    }
+
+    ix
+}
+
+pub fn close_user_stake(keys: &PoolKeys, user: &Pubkey) -> Instruction {
+    build(
+        accounts::CloseUserStake {
+            user: *user,
+            pool: keys.pool,
+            user_stake: keys.user_stake(user),
+            event_authority: event_authority(),
+            program: ID,
+        },
+        instruction::CloseUserStake {},
+    )
+}
+
+/// Unstakes everything and claims the primary reward in one instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn exit(
+    keys: &PoolKeys,
+    user: &Pubkey,
+    user_stake_ata: &Pubkey,
+    user_reward_ata: &Pubkey,
+    stake_token_program: &Pubkey,
+    reward_token_program: &Pubkey,
+    streams: &[StreamClaim],
+    close: bool,
+) -> Instruction {
+    let mut ix = build(
+        accounts::Exit {
+            user: *user,
+            pool: keys.pool,
+            user_stake: keys.user_stake(user),
+            stake_mint: keys.stake_mint,
+            reward_mint: keys.reward_mint,
+            user_stake_ata: *user_stake_ata,
+            user_reward_ata: *user_reward_ata,
+            stake_vault: keys.stake_vault,
+            reward_vault: keys.reward_vault,
+            stake_token_program: *stake_token_program,
+            reward_token_program: *reward_token_program,
+            event_authority: event_authority(),
+            program: ID,
+        },
+        instruction::Exit { close },
+    );
+
+    for stream in streams {
+        ix.accounts.extend([
+            AccountMeta::new(keys.stream_vault(&stream.mint), false),
+            AccountMeta::new(stream.user_reward_ata, false),
+            AccountMeta::new_readonly(stream.mint, false),
+            AccountMeta::new_readonly(stream.token_program, false),
+        ]);
+    }
+
+    ix
+}
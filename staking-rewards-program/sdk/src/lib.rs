@@ -0,0 +1,19 @@
+//! Client helpers for the staking rewards program: PDA derivation, instruction
+//! builders, account decoding, the program's reward math and an offline reward
+//! estimator.
+//!
+//! Everything is built on the program crate itself, so account layouts,
+//! instruction encodings and reward math cannot drift from what runs on-chain.
+
+pub mod accounts;
+pub mod estimate;
+pub mod instructions;
+pub mod pda;
+
+pub use staking_rewards_program::{
+    self as program, math, LockupTier, PendingRewards, PendingStreamReward, Pool, UserStake, ID,
+};
+
+pub use accounts::{decode_pool, decode_user_stake};
+pub use estimate::estimate_pending_rewards;
+pub use pda::PoolKeys;
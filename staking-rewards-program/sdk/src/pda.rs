@@ -0,0 +1,63 @@
+use anchor_lang::prelude::Pubkey;
+use staking_rewards_program::{
+    ID, POOL_SEED, STREAM_VAULT_SEED, USER_SEED, VAULT_REWARD_SEED, VAULT_STAKE_SEED,
+};
+
+/// Seed of the PDA that signs `emit_cpi!` events.
+const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+pub fn pool(stake_mint: &Pubkey, reward_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[POOL_SEED, stake_mint.as_ref(), reward_mint.as_ref()], &ID)
+}
+
+pub fn stake_vault(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_STAKE_SEED, pool.as_ref()], &ID)
+}
+
+pub fn reward_vault(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_REWARD_SEED, pool.as_ref()], &ID)
+}
+
+pub fn stream_vault(pool: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STREAM_VAULT_SEED, pool.as_ref(), mint.as_ref()], &ID)
+}
+
+pub fn user_stake(pool: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[USER_SEED, pool.as_ref(), owner.as_ref()], &ID)
+}
+
+pub fn event_authority() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EVENT_AUTHORITY_SEED], &ID)
+}
+
+/// Addresses that identify a pool, derived once from its two mints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolKeys {
+    pub stake_mint: Pubkey,
+    pub reward_mint: Pubkey,
+    pub pool: Pubkey,
+    pub stake_vault: Pubkey,
+    pub reward_vault: Pubkey,
+}
+
+impl PoolKeys {
+    pub fn new(stake_mint: Pubkey, reward_mint: Pubkey) -> Self {
+        let (pool, _) = pool(&stake_mint, &reward_mint);
+
+        Self {
+            stake_mint,
+            reward_mint,
+            pool,
+            stake_vault: stake_vault(&pool).0,
+            reward_vault: reward_vault(&pool).0,
+        }
+    }
+
+    pub fn user_stake(&self, owner: &Pubkey) -> Pubkey {
+        user_stake(&self.pool, owner).0
+    }
+
+    pub fn stream_vault(&self, mint: &Pubkey) -> Pubkey {
+        stream_vault(&self.pool, mint).0
+    }
+}
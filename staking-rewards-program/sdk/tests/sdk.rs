@@ -0,0 +1,141 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountSerialize, Discriminator};
+use staking_rewards_sdk::program::{instruction, POOL_SEED};
+use staking_rewards_sdk::{
+    decode_pool, estimate_pending_rewards, instructions, Pool, PoolKeys, UserStake, ID,
+};
+
+fn pool(keys: &PoolKeys, rate: u64, total_staked: u128) -> Pool {
+    Pool {
+        admin: Pubkey::new_unique(),
+        pending_admin: Pubkey::default(),
+        stake_mint: keys.stake_mint,
+        reward_mint: keys.reward_mint,
+        reward_rate_per_second: rate,
+        total_staked,
+        reward_per_token_stored: 0,
+        last_update_time: 1_000,
+        period_finish: 0,
+        reward_stream_count: 0,
+        reward_streams: Default::default(),
+        lockup_tier_count: 0,
+        lockup_tiers: Default::default(),
+        cooldown_seconds: 0,
+        total_unbonding: 0,
+        paused: 0,
+        rewards_unpaid: 0,
+        rewards_owed: 0,
+        bump: 255,
+    }
+}
+
+fn position(owner: Pubkey, staked: u128) -> UserStake {
+    UserStake {
+        owner,
+        amount_staked: staked,
+        effective_stake: staked,
+        locked_amount: 0,
+        lock_end: 0,
+        lock_multiplier_bps: 0,
+        pending_rewards: 0,
+        reward_per_token_paid: 0,
+        last_update: 1_000,
+        stream_rewards: Default::default(),
+        unbonding: Default::default(),
+        bump: 255,
+    }
+}
+
+#[test]
+fn pool_keys_follow_program_seeds() {
+    let keys = PoolKeys::new(Pubkey::new_unique(), Pubkey::new_unique());
+    let (expected, _) = Pubkey::find_program_address(
+        &[
+            POOL_SEED,
+            keys.stake_mint.as_ref(),
+            keys.reward_mint.as_ref(),
+        ],
+        &ID,
+    );
+
+    assert_eq!(keys.pool, expected);
+    assert_ne!(keys.stake_vault, keys.reward_vault);
+}
+
+#[test]
+fn claim_all_appends_four_accounts_per_stream() {
+    let keys = PoolKeys::new(Pubkey::new_unique(), Pubkey::new_unique());
+    let user = Pubkey::new_unique();
+    let stream = instructions::StreamClaim {
+        mint: Pubkey::new_unique(),
+        user_reward_ata: Pubkey::new_unique(),
+        token_program: Pubkey::new_unique(),
+    };
+
+    let single =
+        instructions::claim_rewards(&keys, &user, &Pubkey::new_unique(), &stream.token_program, false);
+    let all = instructions::claim_all_rewards(
+        &keys,
+        &user,
+        &Pubkey::new_unique(),
+        &stream.token_program,
+        &[stream, stream],
+    );
+
+    assert_eq!(all.accounts.len(), single.accounts.len() + 8);
+    assert_eq!(
+        all.accounts[single.accounts.len()].pubkey,
+        keys.stream_vault(&stream.mint)
+    );
+    assert!(all
+        .data
+        .starts_with(instruction::ClaimAllRewards::DISCRIMINATOR));
+}
+
+#[test]
+fn stake_for_targets_the_beneficiarys_position() {
+    let keys = PoolKeys::new(Pubkey::new_unique(), Pubkey::new_unique());
+    let funder = Pubkey::new_unique();
+    let beneficiary = Pubkey::new_unique();
+
+    let ix = instructions::stake_for(
+        &keys,
+        &funder,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &beneficiary,
+        5,
+    );
+
+    assert!(ix
+        .accounts
+        .iter()
+        .any(|meta| meta.pubkey == keys.user_stake(&beneficiary)));
+    assert!(!ix
+        .accounts
+        .iter()
+        .any(|meta| meta.pubkey == keys.user_stake(&funder)));
+}
+
+#[test]
+fn decodes_serialized_pool() {
+    let keys = PoolKeys::new(Pubkey::new_unique(), Pubkey::new_unique());
+    let mut data = Vec::new();
+    pool(&keys, 7, 1).try_serialize(&mut data).unwrap();
+
+    let decoded = decode_pool(&data).unwrap();
+    assert_eq!(decoded.reward_rate_per_second, 7);
+    assert_eq!(decoded.stake_mint, keys.stake_mint);
+}
+
+#[test]
+fn estimates_linear_accrual_for_a_sole_staker() {
+    let keys = PoolKeys::new(Pubkey::new_unique(), Pubkey::new_unique());
+    let pool = pool(&keys, 100, 1_000);
+    let user = position(Pubkey::new_unique(), 1_000);
+
+    let pending = estimate_pending_rewards(&pool, &user, 1_010).unwrap();
+
+    assert_eq!(pending.pending, 1_000);
+    assert!(pending.streams.is_empty());
+}
@@ -3,6 +3,8 @@ import {
   PublicKey,
   SystemProgram,
   Keypair,
+  Transaction,
+  sendAndConfirmTransaction,
 } from "@solana/web3.js";
 import {
   getAssociatedTokenAddress,
@@ -11,6 +13,11 @@ import {
   mintTo,
   getAccount,
   TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  ExtensionType,
+  getMintLen,
+  createInitializeTransferFeeConfigInstruction,
+  createInitializeMintInstruction,
 } from "@solana/spl-token";
 
 import { StakingRewardsProgram } from "../target/types/staking_rewards_program";
@@ -54,6 +61,13 @@ describe("staking-rewards-program", () => {
     );
   };
 
+  const getStreamVaultPda = (pool: PublicKey, mint: PublicKey) => {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("stream_vault"), pool.toBuffer(), mint.toBuffer()],
+      program.programId
+    );
+  };
+
   const getUserStakePda = (pool: PublicKey, user: PublicKey) => {
     return PublicKey.findProgramAddressSync(
       [Buffer.from("user_stake"), pool.toBuffer(), user.toBuffer()],
@@ -105,7 +119,8 @@ describe("staking-rewards-program", () => {
         pool: poolPda,
         stakeVault,
         rewardVault,
-        tokenProgram: TOKEN_PROGRAM_ID,
+        stakeTokenProgram: TOKEN_PROGRAM_ID,
+        rewardTokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
       .rpc();
@@ -174,12 +189,13 @@ describe("staking-rewards-program", () => {
     const adminRewardAta = await getAssociatedTokenAddress(rewardMint, admin);
 
     const tx = await program.methods
-      .depositRewards(new anchor.BN(5_000_000))
+      .depositRewards(new anchor.BN(5_000_000), new anchor.BN(0))
       .accounts({
         admin,
         adminRewardAta,
         rewardVault,
         pool: poolPda,
+        rewardMint,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .rpc();
@@ -191,6 +207,60 @@ describe("staking-rewards-program", () => {
     console.log("Reward Vault Balance:", rewardVaultAccount.amount.toString());
   });
 
+  it("Deposit Rewards over a fixed period", async () => {
+    const adminRewardAta = await getAssociatedTokenAddress(rewardMint, admin);
+    const amount = new anchor.BN(1_000_000);
+    const duration = new anchor.BN(1_000);
+
+    // First period: rate comes purely from the deposit
+    await program.methods
+      .depositRewards(amount, duration)
+      .accounts({
+        admin,
+        adminRewardAta,
+        rewardVault,
+        pool: poolPda,
+        rewardMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const firstPeriod = await program.account.pool.fetch(poolPda);
+    console.log("Period Rate:", firstPeriod.rewardRatePerSecond.toString());
+    console.log("Period Finish:", firstPeriod.periodFinish.toString());
+    if (!firstPeriod.rewardRatePerSecond.eq(amount.div(duration))) {
+      throw new Error("Rate was not derived from the deposit");
+    }
+    if (!firstPeriod.periodFinish.sub(firstPeriod.lastUpdateTime).eq(duration)) {
+      throw new Error("Period finish does not match duration");
+    }
+
+    // Second deposit rolls the undistributed remainder into the new period
+    await program.methods
+      .depositRewards(amount, duration)
+      .accounts({
+        admin,
+        adminRewardAta,
+        rewardVault,
+        pool: poolPda,
+        rewardMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const secondPeriod = await program.account.pool.fetch(poolPda);
+    const leftover = firstPeriod.periodFinish
+      .sub(secondPeriod.lastUpdateTime)
+      .mul(firstPeriod.rewardRatePerSecond);
+    const expectedRate = amount.add(leftover).div(duration);
+    console.log("Rolled Over Rate:", secondPeriod.rewardRatePerSecond.toString());
+    if (!secondPeriod.rewardRatePerSecond.eq(expectedRate)) {
+      throw new Error(
+        `Expected rate ${expectedRate.toString()}, got ${secondPeriod.rewardRatePerSecond.toString()}`
+      );
+    }
+  });
+
   it("Stake Tokens", async () => {
     const user = admin;
 
@@ -210,6 +280,7 @@ describe("staking-rewards-program", () => {
         userStake: userStakePda,
         userStakeAta,
         stakeVault,
+        stakeMint,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
@@ -229,6 +300,13 @@ describe("staking-rewards-program", () => {
     // Verify pool total staked
     const poolAccount = await program.account.pool.fetch(poolPda);
     console.log("Pool Total Staked:", poolAccount.totalStaked.toString());
+
+    // The user is settled against the pool accumulator on every stake
+    console.log("Pool Reward Per Token:", poolAccount.rewardPerTokenStored.toString());
+    console.log("User Reward Per Token Paid:", userStakeAccount.rewardPerTokenPaid.toString());
+    if (!userStakeAccount.rewardPerTokenPaid.eq(poolAccount.rewardPerTokenStored)) {
+      throw new Error("User snapshot does not match pool accumulator");
+    }
   });
 
   it("Claim Rewards", async () => {
@@ -253,13 +331,14 @@ describe("staking-rewards-program", () => {
     }
 
     const tx = await program.methods
-      .claimRewards()
+      .claimRewards(false)
       .accounts({
         user,
         pool: poolPda,
         userStake: userStakePda,
         userRewardAta,
         rewardVault,
+        rewardMint,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .rpc();
@@ -283,13 +362,14 @@ describe("staking-rewards-program", () => {
     const unstakeAmount = new anchor.BN(500_000); // Unstake half
 
     const tx = await program.methods
-      .unstake(unstakeAmount)
+      .unstake(unstakeAmount, false)
       .accounts({
         user,
         pool: poolPda,
         userStake: userStakePda,
         userStakeAta,
         stakeVault,
+        stakeMint,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .rpc();
@@ -309,6 +389,135 @@ describe("staking-rewards-program", () => {
     console.log("Pool Total Staked After Unstake:", poolAccount.totalStaked.toString());
   });
 
+  it("Set Reward Rate checkpoints accrual at the old rate", async () => {
+    const user = admin;
+    const [userStakePda] = getUserStakePda(poolPda, user);
+    const userRewardAta = await getAssociatedTokenAddress(rewardMint, user);
+    const PRECISION = new anchor.BN("1000000000000");
+
+    const expectedAccrual = (
+      rate: anchor.BN,
+      from: anchor.BN,
+      to: anchor.BN,
+      totalStaked: anchor.BN
+    ) => rate.mul(to.sub(from)).mul(PRECISION).div(totalStaked);
+
+    const poolBefore = await program.account.pool.fetch(poolPda);
+    const oldRate = poolBefore.rewardRatePerSecond;
+    // A period is running, so the new rate must be covered until it finishes.
+    // 2000/s is, thanks to the rewards deposited without a period.
+    const newRate = new anchor.BN(2000);
+    const vaultBalance = new anchor.BN(
+      (await getAccount(connection, rewardVault)).amount.toString()
+    );
+
+    try {
+      await program.methods
+        .setRewardRate(vaultBalance)
+        .accounts({
+          admin,
+          pool: poolPda,
+          rewardVault,
+        })
+        .rpc();
+      throw new Error("Unfunded reward rate was accepted");
+    } catch (err) {
+      if (!`${err}`.includes("InsufficientFunds")) {
+        throw err;
+      }
+      console.log("Unfunded rate rejected as expected");
+    }
+
+    await new Promise(resolve => setTimeout(resolve, 2000));
+
+    const tx = await program.methods
+      .setRewardRate(newRate)
+      .accounts({
+        admin,
+        pool: poolPda,
+        rewardVault,
+      })
+      .rpc();
+
+    console.log("Set Reward Rate Tx:", tx);
+
+    // Everything up to the switch accrued at the old rate
+    const poolAtSwitch = await program.account.pool.fetch(poolPda);
+    if (!poolAtSwitch.rewardRatePerSecond.eq(newRate)) {
+      throw new Error("Reward rate was not updated");
+    }
+    const accruedBefore = poolAtSwitch.rewardPerTokenStored.sub(
+      poolBefore.rewardPerTokenStored
+    );
+    const expectedBefore = expectedAccrual(
+      oldRate,
+      poolBefore.lastUpdateTime,
+      poolAtSwitch.lastUpdateTime,
+      poolBefore.totalStaked
+    );
+    console.log("Accrued At Old Rate:", accruedBefore.toString());
+    if (!accruedBefore.eq(expectedBefore)) {
+      throw new Error(
+        `Expected ${expectedBefore.toString()} at old rate, got ${accruedBefore.toString()}`
+      );
+    }
+
+    await new Promise(resolve => setTimeout(resolve, 2000));
+
+    // Claiming checkpoints the pool again, this time at the new rate
+    await program.methods
+      .claimRewards(false)
+      .accounts({
+        user,
+        pool: poolPda,
+        userStake: userStakePda,
+        userRewardAta,
+        rewardVault,
+        rewardMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const poolAfter = await program.account.pool.fetch(poolPda);
+    const accruedAfter = poolAfter.rewardPerTokenStored.sub(
+      poolAtSwitch.rewardPerTokenStored
+    );
+    const expectedAfter = expectedAccrual(
+      newRate,
+      poolAtSwitch.lastUpdateTime,
+      poolAfter.lastUpdateTime,
+      poolAtSwitch.totalStaked
+    );
+    console.log("Accrued At New Rate:", accruedAfter.toString());
+    if (!accruedAfter.eq(expectedAfter)) {
+      throw new Error(
+        `Expected ${expectedAfter.toString()} at new rate, got ${accruedAfter.toString()}`
+      );
+    }
+  });
+
+  it("Set Reward Rate rejects non-admin", async () => {
+    const outsider = Keypair.generate();
+
+    try {
+      await program.methods
+        .setRewardRate(new anchor.BN(1))
+        .accounts({
+          admin: outsider.publicKey,
+          pool: poolPda,
+          rewardVault,
+        })
+        .signers([outsider])
+        .rpc();
+      throw new Error("Non-admin was able to set the reward rate");
+    } catch (err) {
+      if (!`${err}`.includes("Unauthorized")) {
+        throw err;
+      }
+      console.log("Non-admin rejected as expected");
+    }
+  });
+
   it("Test multiple users staking", async () => {
     // Create a second user
     const user2 = Keypair.generate();
@@ -359,6 +568,7 @@ describe("staking-rewards-program", () => {
         userStake: user2StakePda,
         userStakeAta: user2StakeAta,
         stakeVault,
+        stakeMint,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
@@ -378,4 +588,1466 @@ describe("staking-rewards-program", () => {
     const poolAccount = await program.account.pool.fetch(poolPda);
     console.log("Total Staked (Both Users):", poolAccount.totalStaked.toString());
   });
-});
\ No newline at end of file
+
+  it("Claim pays out partially from an underfunded vault", async () => {
+    // Separate pool whose reward vault holds far less than it emits
+    const smallRewardMint = await createMint(
+      connection,
+      wallet.payer,
+      wallet.publicKey,
+      null,
+      6
+    );
+    const [smallPool] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), stakeMint.toBuffer(), smallRewardMint.toBuffer()],
+      program.programId
+    );
+    const [smallStakeVault] = getStakeVaultPda(smallPool);
+    const [smallRewardVault] = getRewardVaultPda(smallPool);
+    const [userStakePda] = getUserStakePda(smallPool, admin);
+
+    await program.methods
+      .initializePool(new anchor.BN(1_000_000))
+      .accounts({
+        admin,
+        stakeMint,
+        rewardMint: smallRewardMint,
+        pool: smallPool,
+        stakeVault: smallStakeVault,
+        rewardVault: smallRewardVault,
+        stakeTokenProgram: TOKEN_PROGRAM_ID,
+        rewardTokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const adminRewardAta = await createAssociatedTokenAccount(
+      connection,
+      wallet.payer,
+      smallRewardMint,
+      admin
+    );
+    await mintTo(
+      connection,
+      wallet.payer,
+      smallRewardMint,
+      adminRewardAta,
+      wallet.payer,
+      1_000
+    );
+
+    await program.methods
+      .depositRewards(new anchor.BN(1_000), new anchor.BN(0))
+      .accounts({
+        admin,
+        adminRewardAta,
+        rewardVault: smallRewardVault,
+        pool: smallPool,
+        rewardMint: smallRewardMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    await program.methods
+      .stake(new anchor.BN(100_000))
+      .accounts({
+        user: admin,
+        pool: smallPool,
+        userStake: userStakePda,
+        userStakeAta: await getAssociatedTokenAddress(stakeMint, admin),
+        stakeVault: smallStakeVault,
+        stakeMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await new Promise(resolve => setTimeout(resolve, 2000));
+
+    const claim = () =>
+      program.methods
+        .claimRewards(false)
+        .accounts({
+          user: admin,
+          pool: smallPool,
+          userStake: userStakePda,
+          userRewardAta: adminRewardAta,
+          rewardVault: smallRewardVault,
+          rewardMint: smallRewardMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    await claim();
+
+    // The vault is drained and the shortfall is carried over
+    const vaultAfter = await getAccount(connection, smallRewardVault);
+    const userAfter = await program.account.userStake.fetch(userStakePda);
+    console.log("Vault After Partial Claim:", vaultAfter.amount.toString());
+    console.log("Carried Over Rewards:", userAfter.pendingRewards.toString());
+    if (vaultAfter.amount !== BigInt(0) || userAfter.pendingRewards.isZero()) {
+      throw new Error("Expected a partial payout with rewards carried over");
+    }
+
+    try {
+      await claim();
+      throw new Error("Claim against an empty vault should fail");
+    } catch (err) {
+      if (!`${err}`.includes("RewardVaultDepleted")) {
+        throw err;
+      }
+      console.log("Empty vault claim rejected as expected");
+    }
+  });
+
+  it("Emits and claims an additional reward stream", async () => {
+    const user = admin;
+    const [userStakePda] = getUserStakePda(poolPda, user);
+    const userRewardAta = await getAssociatedTokenAddress(rewardMint, user);
+
+    const partnerMint = await createMint(
+      connection,
+      wallet.payer,
+      wallet.publicKey,
+      null,
+      6
+    );
+    const [streamVault] = getStreamVaultPda(poolPda, partnerMint);
+    const partnerAta = await createAssociatedTokenAccount(
+      connection,
+      wallet.payer,
+      partnerMint,
+      admin
+    );
+    await mintTo(
+      connection,
+      wallet.payer,
+      partnerMint,
+      partnerAta,
+      wallet.payer,
+      10_000_000
+    );
+
+    await program.methods
+      .addRewardStream(new anchor.BN(500))
+      .accounts({
+        admin,
+        pool: poolPda,
+        rewardMint: partnerMint,
+        streamVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .depositStreamRewards(new anchor.BN(5_000_000), new anchor.BN(0))
+      .accounts({
+        admin,
+        adminRewardAta: partnerAta,
+        streamVault,
+        pool: poolPda,
+        rewardMint: partnerMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const poolAccount = await program.account.pool.fetch(poolPda);
+    console.log("Reward Streams:", poolAccount.rewardStreamCount);
+    if (!poolAccount.rewardStreams[0].mint.equals(partnerMint)) {
+      throw new Error("Reward stream was not registered");
+    }
+
+    await new Promise(resolve => setTimeout(resolve, 2000));
+
+    // Claim just the partner stream
+    await program.methods
+      .claimStreamRewards()
+      .accounts({
+        user,
+        pool: poolPda,
+        userStake: userStakePda,
+        userRewardAta: partnerAta,
+        streamVault,
+        rewardMint: partnerMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const partnerAfterOne = await getAccount(connection, partnerAta);
+    console.log("Partner Balance After Stream Claim:", partnerAfterOne.amount.toString());
+
+    await new Promise(resolve => setTimeout(resolve, 2000));
+
+    // Claim the primary reward and every stream together
+    await program.methods
+      .claimAllRewards()
+      .accounts({
+        user,
+        pool: poolPda,
+        userStake: userStakePda,
+        userRewardAta,
+        rewardVault,
+        rewardMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        { pubkey: streamVault, isWritable: true, isSigner: false },
+        { pubkey: partnerAta, isWritable: true, isSigner: false },
+        { pubkey: partnerMint, isWritable: false, isSigner: false },
+        { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+      ])
+      .rpc();
+
+    const partnerAfterAll = await getAccount(connection, partnerAta);
+    console.log("Partner Balance After Claim All:", partnerAfterAll.amount.toString());
+    if (partnerAfterAll.amount <= partnerAfterOne.amount) {
+      throw new Error("Claim all did not pay the partner stream");
+    }
+  });
+
+  it("Locks stake for a boosted weight", async () => {
+    const user = admin;
+    const [userStakePda] = getUserStakePda(poolPda, user);
+    const userStakeAta = await getAssociatedTokenAddress(stakeMint, user);
+    const DAY = 24 * 60 * 60;
+
+    await program.methods
+      .setLockupTiers([
+        { duration: new anchor.BN(30 * DAY), multiplierBps: 12_500 },
+        { duration: new anchor.BN(4), multiplierBps: 20_000 },
+      ])
+      .accounts({
+        admin,
+        pool: poolPda,
+      })
+      .rpc();
+
+    const poolBefore = await program.account.pool.fetch(poolPda);
+    const userBefore = await program.account.userStake.fetch(userStakePda);
+    const lockAmount = new anchor.BN(100_000);
+
+    await program.methods
+      .lockStake(lockAmount, 1)
+      .accounts({
+        user,
+        pool: poolPda,
+        userStake: userStakePda,
+      })
+      .rpc();
+
+    // Locked principal counts twice at 2x
+    const poolLocked = await program.account.pool.fetch(poolPda);
+    const userLocked = await program.account.userStake.fetch(userStakePda);
+    console.log("Effective Stake While Locked:", userLocked.effectiveStake.toString());
+    if (!userLocked.effectiveStake.eq(userBefore.amountStaked.add(lockAmount))) {
+      throw new Error("Lock did not boost the effective stake");
+    }
+    if (!poolLocked.totalStaked.eq(poolBefore.totalStaked.add(lockAmount))) {
+      throw new Error("Pool total does not track the weighted stake");
+    }
+
+    try {
+      await program.methods
+        .unstake(new anchor.BN(userLocked.amountStaked.toString()), false)
+        .accounts({
+          user,
+          pool: poolPda,
+          userStake: userStakePda,
+          userStakeAta,
+          stakeVault,
+          stakeMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      throw new Error("Locked stake should not be unstakeable");
+    } catch (err) {
+      if (!`${err}`.includes("StakeLocked")) {
+        throw err;
+      }
+      console.log("Locked unstake rejected as expected");
+    }
+
+    await new Promise(resolve => setTimeout(resolve, 5000));
+
+    await program.methods
+      .expireLock()
+      .accounts({
+        pool: poolPda,
+        userStake: userStakePda,
+      })
+      .rpc();
+
+    const userExpired = await program.account.userStake.fetch(userStakePda);
+    console.log("Effective Stake After Expiry:", userExpired.effectiveStake.toString());
+    if (!userExpired.effectiveStake.eq(userExpired.amountStaked)) {
+      throw new Error("Expired lock kept its boost");
+    }
+  });
+
+  it("Unstakes through the cooldown queue", async () => {
+    const user = admin;
+    const [userStakePda] = getUserStakePda(poolPda, user);
+    const userStakeAta = await getAssociatedTokenAddress(stakeMint, user);
+
+    const setCooldown = (seconds: number) =>
+      program.methods
+        .setCooldown(new anchor.BN(seconds))
+        .accounts({
+          admin,
+          pool: poolPda,
+        })
+        .rpc();
+
+    const unstakeAccounts = {
+      user,
+      pool: poolPda,
+      userStake: userStakePda,
+      userStakeAta,
+      stakeMint,
+      stakeVault,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+
+    await setCooldown(3);
+
+    try {
+      await program.methods
+        .unstake(new anchor.BN(10_000), false)
+        .accounts(unstakeAccounts)
+        .rpc();
+      throw new Error("Instant unstake should be disabled during cooldown");
+    } catch (err) {
+      if (!`${err}`.includes("CooldownRequired")) {
+        throw err;
+      }
+    }
+
+    const userBefore = await program.account.userStake.fetch(userStakePda);
+    const requestAmount = new anchor.BN(50_000);
+
+    await program.methods
+      .requestUnstake(requestAmount)
+      .accounts({
+        user,
+        pool: poolPda,
+        userStake: userStakePda,
+      })
+      .rpc();
+
+    const userRequested = await program.account.userStake.fetch(userStakePda);
+    console.log("Unbonding Entry:", userRequested.unbonding[0].amount.toString());
+    if (!userRequested.amountStaked.eq(userBefore.amountStaked.sub(requestAmount))) {
+      throw new Error("Requested amount is still earning");
+    }
+
+    try {
+      await program.methods.withdrawUnbonded().accounts(unstakeAccounts).rpc();
+      throw new Error("Withdrawal before the cooldown should fail");
+    } catch (err) {
+      if (!`${err}`.includes("NothingToWithdraw")) {
+        throw err;
+      }
+    }
+
+    await new Promise(resolve => setTimeout(resolve, 4000));
+
+    const ataBefore = await getAccount(connection, userStakeAta);
+    await program.methods.withdrawUnbonded().accounts(unstakeAccounts).rpc();
+    const ataAfter = await getAccount(connection, userStakeAta);
+    console.log(
+      "Withdrawn After Cooldown:",
+      (ataAfter.amount - ataBefore.amount).toString()
+    );
+    if (ataAfter.amount - ataBefore.amount !== BigInt(50_000)) {
+      throw new Error("Unbonded amount was not paid out");
+    }
+
+    await setCooldown(0);
+  });
+
+  it("Emergency unstake returns principal and forfeits rewards", async () => {
+    const user3 = Keypair.generate();
+    const airdropSig = await connection.requestAirdrop(user3.publicKey, 2_000_000_000);
+    await connection.confirmTransaction(airdropSig);
+
+    const user3StakeAta = await createAssociatedTokenAccount(
+      connection,
+      wallet.payer,
+      stakeMint,
+      user3.publicKey
+    );
+    await mintTo(
+      connection,
+      wallet.payer,
+      stakeMint,
+      user3StakeAta,
+      wallet.payer,
+      1_000_000
+    );
+
+    const [user3StakePda] = getUserStakePda(poolPda, user3.publicKey);
+    const accounts = {
+      user: user3.publicKey,
+      pool: poolPda,
+      userStake: user3StakePda,
+      userStakeAta: user3StakeAta,
+      stakeMint,
+      stakeVault,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+
+    await program.methods
+      .stake(new anchor.BN(1_000_000))
+      .accounts({ ...accounts, systemProgram: SystemProgram.programId })
+      .signers([user3])
+      .rpc();
+
+    const poolBefore = await program.account.pool.fetch(poolPda);
+
+    await new Promise(resolve => setTimeout(resolve, 2000));
+
+    await program.methods
+      .emergencyUnstake()
+      .accounts(accounts)
+      .signers([user3])
+      .rpc();
+
+    const ata = await getAccount(connection, user3StakeAta);
+    const position = await program.account.userStake.fetch(user3StakePda);
+    const poolAfter = await program.account.pool.fetch(poolPda);
+    console.log("Principal Returned:", ata.amount.toString());
+    if (ata.amount !== BigInt(1_000_000)) {
+      throw new Error("Emergency unstake did not return the full principal");
+    }
+    if (!position.amountStaked.isZero() || !position.pendingRewards.isZero()) {
+      throw new Error("Position was not cleared");
+    }
+    if (!poolAfter.totalStaked.eq(poolBefore.totalStaked.sub(new anchor.BN(1_000_000)))) {
+      throw new Error("Pool total staked is inconsistent");
+    }
+  });
+
+  it("Pause halts staking until unpaused", async () => {
+    const user = admin;
+    const [userStakePda] = getUserStakePda(poolPda, user);
+    const userStakeAta = await getAssociatedTokenAddress(stakeMint, user);
+    const PAUSE_STAKE = 1;
+    const PAUSE_ALL = 7;
+
+    const stake = () =>
+      program.methods
+        .stake(new anchor.BN(1_000))
+        .accounts({
+          user,
+          pool: poolPda,
+          userStake: userStakePda,
+          userStakeAta,
+          stakeVault,
+          stakeMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    await program.methods
+      .pause(PAUSE_ALL)
+      .accounts({ admin, pool: poolPda })
+      .rpc();
+
+    const paused = await program.account.pool.fetch(poolPda);
+    console.log("Paused Flags:", paused.paused);
+
+    try {
+      await stake();
+      throw new Error("Stake should be rejected while paused");
+    } catch (err) {
+      if (!`${err}`.includes("Paused")) {
+        throw err;
+      }
+      console.log("Stake rejected while paused");
+    }
+
+    await program.methods
+      .unpause(PAUSE_STAKE)
+      .accounts({ admin, pool: poolPda })
+      .rpc();
+    await stake();
+
+    await program.methods
+      .unpause(PAUSE_ALL)
+      .accounts({ admin, pool: poolPda })
+      .rpc();
+
+    const unpaused = await program.account.pool.fetch(poolPda);
+    if (unpaused.paused !== 0) {
+      throw new Error("Pool is still paused");
+    }
+  });
+
+  it("Transfers admin in two steps", async () => {
+    const newAdmin = Keypair.generate();
+    const outsider = Keypair.generate();
+
+    await program.methods
+      .proposeAdmin(newAdmin.publicKey)
+      .accounts({ admin, pool: poolPda })
+      .rpc();
+
+    const proposed = await program.account.pool.fetch(poolPda);
+    if (!proposed.admin.equals(admin) || !proposed.pendingAdmin.equals(newAdmin.publicKey)) {
+      throw new Error("Proposal should not change the admin yet");
+    }
+
+    try {
+      await program.methods
+        .acceptAdmin()
+        .accounts({ newAdmin: outsider.publicKey, pool: poolPda })
+        .signers([outsider])
+        .rpc();
+      throw new Error("Only the proposed key can accept");
+    } catch (err) {
+      if (!`${err}`.includes("Unauthorized")) {
+        throw err;
+      }
+    }
+
+    await program.methods
+      .acceptAdmin()
+      .accounts({ newAdmin: newAdmin.publicKey, pool: poolPda })
+      .signers([newAdmin])
+      .rpc();
+
+    const accepted = await program.account.pool.fetch(poolPda);
+    console.log("New Admin:", accepted.admin.toBase58());
+    if (!accepted.admin.equals(newAdmin.publicKey)) {
+      throw new Error("Admin was not transferred");
+    }
+
+    // Hand control back so the wallet stays admin for later tests
+    await program.methods
+      .proposeAdmin(admin)
+      .accounts({ admin: newAdmin.publicKey, pool: poolPda })
+      .signers([newAdmin])
+      .rpc();
+    await program.methods
+      .acceptAdmin()
+      .accounts({ newAdmin: admin, pool: poolPda })
+      .rpc();
+  });
+
+  it("Compounds rewards in a same-mint pool", async () => {
+    const [samePool] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), stakeMint.toBuffer(), stakeMint.toBuffer()],
+      program.programId
+    );
+    const [sameStakeVault] = getStakeVaultPda(samePool);
+    const [sameRewardVault] = getRewardVaultPda(samePool);
+    const [userStakePda] = getUserStakePda(samePool, admin);
+    const adminStakeAta = await getAssociatedTokenAddress(stakeMint, admin);
+
+    await program.methods
+      .initializePool(new anchor.BN(1_000))
+      .accounts({
+        admin,
+        stakeMint,
+        rewardMint: stakeMint,
+        pool: samePool,
+        stakeVault: sameStakeVault,
+        rewardVault: sameRewardVault,
+        stakeTokenProgram: TOKEN_PROGRAM_ID,
+        rewardTokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .depositRewards(new anchor.BN(1_000_000), new anchor.BN(0))
+      .accounts({
+        admin,
+        adminRewardAta: adminStakeAta,
+        rewardVault: sameRewardVault,
+        pool: samePool,
+        rewardMint: stakeMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    await program.methods
+      .stake(new anchor.BN(100_000))
+      .accounts({
+        user: admin,
+        pool: samePool,
+        userStake: userStakePda,
+        userStakeAta: adminStakeAta,
+        stakeVault: sameStakeVault,
+        stakeMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await new Promise(resolve => setTimeout(resolve, 2000));
+
+    await program.methods
+      .compound()
+      .accounts({
+        user: admin,
+        pool: samePool,
+        userStake: userStakePda,
+        stakeVault: sameStakeVault,
+        rewardVault: sameRewardVault,
+        stakeMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const position = await program.account.userStake.fetch(userStakePda);
+    const vault = await getAccount(connection, sameStakeVault);
+    console.log("Staked After Compound:", position.amountStaked.toString());
+    if (position.amountStaked.lten(100_000)) {
+      throw new Error("Rewards were not restaked");
+    }
+    if (vault.amount.toString() !== position.amountStaked.toString()) {
+      throw new Error("Stake vault does not back the compounded position");
+    }
+
+    // Pools with different mints cannot compound
+    try {
+      await program.methods
+        .compound()
+        .accounts({
+          user: admin,
+          pool: poolPda,
+          userStake: getUserStakePda(poolPda, admin)[0],
+          stakeVault,
+          rewardVault,
+          stakeMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      throw new Error("Compound should require matching mints");
+    } catch (err) {
+      if (!`${err}`.includes("MintMismatch")) {
+        throw err;
+      }
+    }
+  });
+
+  it("Stakes and claims with Token-2022 mints", async () => {
+    const stakeMint22 = await createMint(
+      connection,
+      wallet.payer,
+      wallet.publicKey,
+      null,
+      6,
+      undefined,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const rewardMint22 = await createMint(
+      connection,
+      wallet.payer,
+      wallet.publicKey,
+      null,
+      6,
+      undefined,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const [pool22] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), stakeMint22.toBuffer(), rewardMint22.toBuffer()],
+      program.programId
+    );
+    const [stakeVault22] = getStakeVaultPda(pool22);
+    const [rewardVault22] = getRewardVaultPda(pool22);
+    const [userStakePda] = getUserStakePda(pool22, admin);
+
+    const createAta = (mint: PublicKey) =>
+      createAssociatedTokenAccount(
+        connection,
+        wallet.payer,
+        mint,
+        admin,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+    const adminStakeAta = await createAta(stakeMint22);
+    const adminRewardAta = await createAta(rewardMint22);
+    for (const [mint, ata] of [
+      [stakeMint22, adminStakeAta],
+      [rewardMint22, adminRewardAta],
+    ]) {
+      await mintTo(
+        connection,
+        wallet.payer,
+        mint,
+        ata,
+        wallet.payer,
+        1_000_000,
+        [],
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+    }
+
+    await program.methods
+      .initializePool(new anchor.BN(1_000))
+      .accounts({
+        admin,
+        stakeMint: stakeMint22,
+        rewardMint: rewardMint22,
+        pool: pool22,
+        stakeVault: stakeVault22,
+        rewardVault: rewardVault22,
+        stakeTokenProgram: TOKEN_2022_PROGRAM_ID,
+        rewardTokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .depositRewards(new anchor.BN(1_000_000), new anchor.BN(0))
+      .accounts({
+        admin,
+        adminRewardAta,
+        rewardVault: rewardVault22,
+        pool: pool22,
+        rewardMint: rewardMint22,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .rpc();
+
+    await program.methods
+      .stake(new anchor.BN(500_000))
+      .accounts({
+        user: admin,
+        pool: pool22,
+        userStake: userStakePda,
+        userStakeAta: adminStakeAta,
+        stakeVault: stakeVault22,
+        stakeMint: stakeMint22,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await new Promise(resolve => setTimeout(resolve, 2000));
+
+    await program.methods
+      .claimRewards(false)
+      .accounts({
+        user: admin,
+        pool: pool22,
+        userStake: userStakePda,
+        userRewardAta: adminRewardAta,
+        rewardVault: rewardVault22,
+        rewardMint: rewardMint22,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .rpc();
+
+    const stakeVaultAccount = await getAccount(
+      connection,
+      stakeVault22,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const rewardAccount = await getAccount(
+      connection,
+      adminRewardAta,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    console.log("Token-2022 Stake Vault:", stakeVaultAccount.amount.toString());
+    console.log("Token-2022 Rewards Claimed:", rewardAccount.amount.toString());
+    if (stakeVaultAccount.amount !== BigInt(500_000)) {
+      throw new Error("Token-2022 stake was not deposited");
+    }
+    if (rewardAccount.amount === BigInt(0)) {
+      throw new Error("Token-2022 rewards were not paid");
+    }
+  });
+
+  it("Credits net amounts for transfer-fee mints", async () => {
+    // 1% fee on every transfer of either mint
+    const FEE_BPS = 100;
+    const createFeeMint = async () => {
+      const mint = Keypair.generate();
+      const space = getMintLen([ExtensionType.TransferFeeConfig]);
+      const lamports = await connection.getMinimumBalanceForRentExemption(space);
+      await sendAndConfirmTransaction(
+        connection,
+        new Transaction().add(
+          SystemProgram.createAccount({
+            fromPubkey: admin,
+            newAccountPubkey: mint.publicKey,
+            space,
+            lamports,
+            programId: TOKEN_2022_PROGRAM_ID,
+          }),
+          createInitializeTransferFeeConfigInstruction(
+            mint.publicKey,
+            admin,
+            admin,
+            FEE_BPS,
+            BigInt(1_000_000_000),
+            TOKEN_2022_PROGRAM_ID
+          ),
+          createInitializeMintInstruction(
+            mint.publicKey,
+            6,
+            admin,
+            null,
+            TOKEN_2022_PROGRAM_ID
+          )
+        ),
+        [wallet.payer, mint]
+      );
+      return mint.publicKey;
+    };
+
+    const feeStakeMint = await createFeeMint();
+    const feeRewardMint = await createFeeMint();
+    const [feePool] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), feeStakeMint.toBuffer(), feeRewardMint.toBuffer()],
+      program.programId
+    );
+    const [feeStakeVault] = getStakeVaultPda(feePool);
+    const [feeRewardVault] = getRewardVaultPda(feePool);
+    const [userStakePda] = getUserStakePda(feePool, admin);
+
+    const createAta = (mint: PublicKey) =>
+      createAssociatedTokenAccount(
+        connection,
+        wallet.payer,
+        mint,
+        admin,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+    const adminStakeAta = await createAta(feeStakeMint);
+    const adminRewardAta = await createAta(feeRewardMint);
+    for (const [mint, ata] of [
+      [feeStakeMint, adminStakeAta],
+      [feeRewardMint, adminRewardAta],
+    ]) {
+      await mintTo(
+        connection,
+        wallet.payer,
+        mint,
+        ata,
+        wallet.payer,
+        10_000_000,
+        [],
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+    }
+    const balanceOf = async (account: PublicKey) =>
+      (await getAccount(connection, account, undefined, TOKEN_2022_PROGRAM_ID)).amount;
+
+    await program.methods
+      .initializePool(new anchor.BN(1))
+      .accounts({
+        admin,
+        stakeMint: feeStakeMint,
+        rewardMint: feeRewardMint,
+        pool: feePool,
+        stakeVault: feeStakeVault,
+        rewardVault: feeRewardVault,
+        stakeTokenProgram: TOKEN_2022_PROGRAM_ID,
+        rewardTokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    // The period rate is derived from what the vault received, not what was sent
+    const duration = new anchor.BN(1_000);
+    await program.methods
+      .depositRewards(new anchor.BN(1_000_000), duration)
+      .accounts({
+        admin,
+        adminRewardAta,
+        rewardVault: feeRewardVault,
+        pool: feePool,
+        rewardMint: feeRewardMint,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .rpc();
+
+    const poolFunded = await program.account.pool.fetch(feePool);
+    console.log("Rate From Net Deposit:", poolFunded.rewardRatePerSecond.toString());
+    if (!poolFunded.rewardRatePerSecond.eqn(990_000 / 1_000)) {
+      throw new Error("Deposit rate was not derived from the net amount");
+    }
+
+    const unstakeAccounts = {
+      user: admin,
+      pool: feePool,
+      userStake: userStakePda,
+      userStakeAta: adminStakeAta,
+      stakeMint: feeStakeMint,
+      stakeVault: feeStakeVault,
+      tokenProgram: TOKEN_2022_PROGRAM_ID,
+    };
+
+    await program.methods
+      .stake(new anchor.BN(100_000))
+      .accounts({ ...unstakeAccounts, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    const position = await program.account.userStake.fetch(userStakePda);
+    const pool = await program.account.pool.fetch(feePool);
+    const vault = await balanceOf(feeStakeVault);
+    console.log("Net Amount Staked:", position.amountStaked.toString());
+    if (!position.amountStaked.eqn(99_000)) {
+      throw new Error("Stake credited the gross amount");
+    }
+    if (pool.totalStaked.toString() !== vault.toString()) {
+      throw new Error("Pool total staked exceeds the vault balance");
+    }
+
+    // Outbound fees are withheld from the recipient, the vault pays in full
+    const ataBefore = await balanceOf(adminStakeAta);
+    await program.methods
+      .unstake(new anchor.BN(50_000), false)
+      .accounts(unstakeAccounts)
+      .rpc();
+    const ataAfter = await balanceOf(adminStakeAta);
+    const vaultAfter = await balanceOf(feeStakeVault);
+    console.log("Received After Fee:", (ataAfter - ataBefore).toString());
+    if (ataAfter - ataBefore !== BigInt(49_500)) {
+      throw new Error("Unstake fee was not withheld from the recipient");
+    }
+    if (vaultAfter !== BigInt(49_000)) {
+      throw new Error("Vault no longer backs the remaining stake");
+    }
+  });
+
+  it("Emits CPI events for stake and unstake", async () => {
+    const user = admin;
+    const [userStakePda] = getUserStakePda(poolPda, user);
+    const userStakeAta = await getAssociatedTokenAddress(stakeMint, user);
+    const accounts = {
+      user,
+      pool: poolPda,
+      userStake: userStakePda,
+      userStakeAta,
+      stakeMint,
+      stakeVault,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+
+    // emit_cpi! events travel as self-invoked instruction data, not logs
+    const cpiEvents = async (signature: string) => {
+      const tx = await connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      return (tx?.meta?.innerInstructions ?? [])
+        .flatMap((inner) => inner.instructions)
+        .map((ix) => {
+          const data = anchor.utils.bytes.bs58.decode(ix.data);
+          return program.coder.events.decode(
+            anchor.utils.bytes.base64.encode(data.subarray(8))
+          );
+        })
+        .filter((event) => event !== null);
+    };
+
+    const stakeSig = await program.methods
+      .stake(new anchor.BN(10_000))
+      .accounts({ ...accounts, systemProgram: SystemProgram.programId })
+      .rpc({ commitment: "confirmed" });
+    const staked = (await cpiEvents(stakeSig)).find(
+      (e) => e.name.toLowerCase() === "staked"
+    );
+    const poolAfterStake = await program.account.pool.fetch(poolPda);
+    console.log("Staked Event:", JSON.stringify(staked?.data));
+    if (!staked || !staked.data.amount.eqn(10_000)) {
+      throw new Error("Staked event was not emitted");
+    }
+    if (!staked.data.totalStaked.eq(poolAfterStake.totalStaked)) {
+      throw new Error("Staked event total does not match the pool");
+    }
+
+    const unstakeSig = await program.methods
+      .unstake(new anchor.BN(10_000), false)
+      .accounts(accounts)
+      .rpc({ commitment: "confirmed" });
+    const unstaked = (await cpiEvents(unstakeSig)).find(
+      (e) => e.name.toLowerCase() === "unstaked"
+    );
+    console.log("Unstaked Event:", JSON.stringify(unstaked?.data));
+    if (!unstaked || !unstaked.data.user.equals(user)) {
+      throw new Error("Unstaked event was not emitted");
+    }
+  });
+
+  it("Views pending rewards without settling", async () => {
+    const [userStakePda] = getUserStakePda(poolPda, admin);
+    const before = await program.account.userStake.fetch(userStakePda);
+
+    await new Promise(resolve => setTimeout(resolve, 2000));
+
+    // Simulated, so the answer comes back through return data
+    const view = await program.methods
+      .viewPendingRewards()
+      .accounts({ pool: poolPda, userStake: userStakePda })
+      .view();
+
+    const after = await program.account.userStake.fetch(userStakePda);
+    const pool = await program.account.pool.fetch(poolPda);
+    console.log("Viewed Pending:", view.pending.toString());
+    console.log("Stored Pending:", after.pendingRewards.toString());
+
+    if (!after.pendingRewards.eq(before.pendingRewards)) {
+      throw new Error("View must not settle rewards");
+    }
+    if (view.pending.lte(after.pendingRewards)) {
+      throw new Error("View did not include unsettled accrual");
+    }
+    if (!view.rewardMint.equals(rewardMint)) {
+      throw new Error("View reported the wrong reward mint");
+    }
+    if (view.streams.length !== pool.rewardStreamCount) {
+      throw new Error("View should report every active stream");
+    }
+  });
+
+  it("Withdraws only excess rewards", async () => {
+    const adminRewardAta = await getAssociatedTokenAddress(rewardMint, admin);
+    const accounts = {
+      admin,
+      pool: poolPda,
+      rewardMint,
+      adminRewardAta,
+      rewardVault,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+
+    // Top up without starting a new period, so the whole deposit is spare
+    await program.methods
+      .depositRewards(new anchor.BN(1_000_000), new anchor.BN(0))
+      .accounts({ ...accounts })
+      .rpc();
+
+    const vaultBefore = await getAccount(connection, rewardVault);
+    await program.methods
+      .withdrawExcessRewards(new anchor.BN(500_000))
+      .accounts(accounts)
+      .rpc();
+    const vaultAfter = await getAccount(connection, rewardVault);
+    console.log("Reward Vault After Withdrawal:", vaultAfter.amount.toString());
+
+    if (vaultBefore.amount - vaultAfter.amount !== BigInt(500_000)) {
+      throw new Error("Excess withdrawal moved the wrong amount");
+    }
+
+    // Stakers have accrued rewards, so the vault can never be emptied
+    try {
+      await program.methods
+        .withdrawExcessRewards(new anchor.BN(vaultAfter.amount.toString()))
+        .accounts(accounts)
+        .rpc();
+      throw new Error("Withdrew rewards owed to stakers");
+    } catch (err) {
+      if (!`${err}`.includes("RewardsCommitted")) {
+        throw err;
+      }
+      console.log("Committed rewards kept as expected");
+    }
+  });
+
+  it("Closes a finished pool and returns its rent", async () => {
+    const closingRewardMint = await createMint(
+      connection,
+      wallet.payer,
+      wallet.publicKey,
+      null,
+      6
+    );
+    const [closingPool] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), stakeMint.toBuffer(), closingRewardMint.toBuffer()],
+      program.programId
+    );
+    const [closingStakeVault] = getStakeVaultPda(closingPool);
+    const [closingRewardVault] = getRewardVaultPda(closingPool);
+    const [userStakePda] = getUserStakePda(closingPool, admin);
+    const adminStakeAta = await getAssociatedTokenAddress(stakeMint, admin);
+
+    await program.methods
+      .initializePool(new anchor.BN(1))
+      .accounts({
+        admin,
+        stakeMint,
+        rewardMint: closingRewardMint,
+        pool: closingPool,
+        stakeVault: closingStakeVault,
+        rewardVault: closingRewardVault,
+        stakeTokenProgram: TOKEN_PROGRAM_ID,
+        rewardTokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const adminRewardAta = await createAssociatedTokenAccount(
+      connection,
+      wallet.payer,
+      closingRewardMint,
+      admin
+    );
+    await mintTo(
+      connection,
+      wallet.payer,
+      closingRewardMint,
+      adminRewardAta,
+      wallet.payer,
+      1_000
+    );
+    await program.methods
+      .depositRewards(new anchor.BN(1_000), new anchor.BN(0))
+      .accounts({
+        admin,
+        adminRewardAta,
+        rewardVault: closingRewardVault,
+        pool: closingPool,
+        rewardMint: closingRewardMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const positionAccounts = {
+      user: admin,
+      pool: closingPool,
+      userStake: userStakePda,
+      userStakeAta: adminStakeAta,
+      stakeVault: closingStakeVault,
+      stakeMint,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+    await program.methods
+      .stake(new anchor.BN(1_000))
+      .accounts({ ...positionAccounts, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    const close = () =>
+      program.methods
+        .closePool()
+        .accounts({
+          admin,
+          pool: closingPool,
+          stakeMint,
+          rewardMint: closingRewardMint,
+          adminStakeAta,
+          adminRewardAta,
+          stakeVault: closingStakeVault,
+          rewardVault: closingRewardVault,
+          stakeTokenProgram: TOKEN_PROGRAM_ID,
+          rewardTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+    const expectCloseToFail = async (code: string) => {
+      try {
+        await close();
+        throw new Error(`Closed a pool despite ${code}`);
+      } catch (err) {
+        if (!`${err}`.includes(code)) {
+          throw err;
+        }
+        console.log(`Close rejected with ${code} as expected`);
+      }
+    };
+
+    await expectCloseToFail("PoolNotEmpty");
+
+    await new Promise(resolve => setTimeout(resolve, 2000));
+    await program.methods
+      .unstake(new anchor.BN(1_000), false)
+      .accounts(positionAccounts)
+      .rpc();
+
+    // Unstaked, but the rewards accrued meanwhile are still owed
+    await expectCloseToFail("RewardsOutstanding");
+
+    await program.methods
+      .claimRewards(false)
+      .accounts({
+        user: admin,
+        pool: closingPool,
+        userStake: userStakePda,
+        userRewardAta: adminRewardAta,
+        rewardVault: closingRewardVault,
+        rewardMint: closingRewardMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    await close();
+
+    // Everything not paid to the staker came back to the admin
+    const adminRewards = await getAccount(connection, adminRewardAta);
+    console.log("Admin Rewards After Close:", adminRewards.amount.toString());
+    if (adminRewards.amount !== BigInt(1_000)) {
+      throw new Error("Leftover rewards were not swept to the admin");
+    }
+    for (const closed of [closingPool, closingStakeVault, closingRewardVault]) {
+      if ((await connection.getAccountInfo(closed)) !== null) {
+        throw new Error(`${closed.toBase58()} was not closed`);
+      }
+    }
+  });
+
+  it("Closes an emptied position on the last claim", async () => {
+    const user = Keypair.generate();
+    const airdropSig = await connection.requestAirdrop(user.publicKey, 1_000_000_000);
+    await connection.confirmTransaction(airdropSig);
+    const userStakeAta = await createAssociatedTokenAccount(
+      connection,
+      wallet.payer,
+      stakeMint,
+      user.publicKey
+    );
+    const userRewardAta = await createAssociatedTokenAccount(
+      connection,
+      wallet.payer,
+      rewardMint,
+      user.publicKey
+    );
+    await mintTo(connection, wallet.payer, stakeMint, userStakeAta, wallet.payer, 10_000);
+
+    const [userStakePda] = getUserStakePda(poolPda, user.publicKey);
+    const stakeAccounts = {
+      user: user.publicKey,
+      pool: poolPda,
+      userStake: userStakePda,
+      userStakeAta,
+      stakeVault,
+      stakeMint,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+    const claimAccounts = {
+      user: user.publicKey,
+      pool: poolPda,
+      userStake: userStakePda,
+      userRewardAta,
+      rewardVault,
+      rewardMint,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+
+    await program.methods
+      .stake(new anchor.BN(10_000))
+      .accounts({ ...stakeAccounts, systemProgram: SystemProgram.programId })
+      .signers([user])
+      .rpc();
+    await new Promise(resolve => setTimeout(resolve, 2000));
+
+    // Rewards are still pending, so asking to close here is a no-op
+    await program.methods
+      .unstake(new anchor.BN(10_000), true)
+      .accounts(stakeAccounts)
+      .signers([user])
+      .rpc();
+    if ((await connection.getAccountInfo(userStakePda)) === null) {
+      throw new Error("Closed a position that still had rewards pending");
+    }
+
+    try {
+      await program.methods
+        .closeUserStake()
+        .accounts({ user: user.publicKey, pool: poolPda, userStake: userStakePda })
+        .signers([user])
+        .rpc();
+      throw new Error("Closed a position that still had rewards pending");
+    } catch (err) {
+      if (!`${err}`.includes("PositionNotEmpty")) {
+        throw err;
+      }
+      console.log("Non-empty position kept as expected");
+    }
+
+    const lamportsBefore = await connection.getBalance(user.publicKey);
+    await program.methods
+      .claimRewards(true)
+      .accounts(claimAccounts)
+      .signers([user])
+      .rpc();
+    const lamportsAfter = await connection.getBalance(user.publicKey);
+    console.log("Rent Refunded:", lamportsAfter - lamportsBefore);
+
+    if ((await connection.getAccountInfo(userStakePda)) !== null) {
+      throw new Error("Emptied position was not closed");
+    }
+    if (lamportsAfter <= lamportsBefore) {
+      throw new Error("Position rent was not refunded");
+    }
+  });
+
+  it("Exits the pool in one call", async () => {
+    const user = Keypair.generate();
+    const airdropSig = await connection.requestAirdrop(user.publicKey, 1_000_000_000);
+    await connection.confirmTransaction(airdropSig);
+
+    const userStakeAta = await createAssociatedTokenAccount(
+      connection,
+      wallet.payer,
+      stakeMint,
+      user.publicKey
+    );
+    const userRewardAta = await createAssociatedTokenAccount(
+      connection,
+      wallet.payer,
+      rewardMint,
+      user.publicKey
+    );
+    await mintTo(connection, wallet.payer, stakeMint, userStakeAta, wallet.payer, 10_000);
+
+    const [userStakePda] = getUserStakePda(poolPda, user.publicKey);
+    await program.methods
+      .stake(new anchor.BN(10_000))
+      .accounts({
+        user: user.publicKey,
+        pool: poolPda,
+        userStake: userStakePda,
+        userStakeAta,
+        stakeVault,
+        stakeMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+    await new Promise(resolve => setTimeout(resolve, 2000));
+
+    // Every active stream is paid too, through its remaining-accounts group
+    const poolAccount = await program.account.pool.fetch(poolPda);
+    const streamAccounts = [];
+    for (const stream of poolAccount.rewardStreams.slice(0, poolAccount.rewardStreamCount)) {
+      const [streamVault] = getStreamVaultPda(poolPda, stream.mint);
+      const streamAta = await createAssociatedTokenAccount(
+        connection,
+        wallet.payer,
+        stream.mint,
+        user.publicKey
+      );
+      streamAccounts.push(
+        { pubkey: streamVault, isWritable: true, isSigner: false },
+        { pubkey: streamAta, isWritable: true, isSigner: false },
+        { pubkey: stream.mint, isWritable: false, isSigner: false },
+        { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false }
+      );
+    }
+
+    await program.methods
+      .exit(true)
+      .accounts({
+        user: user.publicKey,
+        pool: poolPda,
+        userStake: userStakePda,
+        stakeMint,
+        rewardMint,
+        userStakeAta,
+        userRewardAta,
+        stakeVault,
+        rewardVault,
+        stakeTokenProgram: TOKEN_PROGRAM_ID,
+        rewardTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(streamAccounts)
+      .signers([user])
+      .rpc();
+
+    const staked = await getAccount(connection, userStakeAta);
+    const rewards = await getAccount(connection, userRewardAta);
+    console.log("Rewards Paid On Exit:", rewards.amount.toString());
+
+    if (staked.amount !== BigInt(10_000)) {
+      throw new Error("Exit did not return the full principal");
+    }
+    if (rewards.amount === BigInt(0)) {
+      throw new Error("Exit did not pay accrued rewards");
+    }
+    if ((await connection.getAccountInfo(userStakePda)) !== null) {
+      throw new Error("Exit with close did not close the position");
+    }
+  });
+
+  it("Stakes on behalf of a beneficiary", async () => {
+    const beneficiary = Keypair.generate();
+    const airdropSig = await connection.requestAirdrop(beneficiary.publicKey, 1_000_000_000);
+    await connection.confirmTransaction(airdropSig);
+
+    const funderStakeAta = await getAssociatedTokenAddress(stakeMint, admin);
+    const beneficiaryStakeAta = await createAssociatedTokenAccount(
+      connection,
+      wallet.payer,
+      stakeMint,
+      beneficiary.publicKey
+    );
+    const [userStakePda] = getUserStakePda(poolPda, beneficiary.publicKey);
+
+    // The treasury signs and pays; the beneficiary does not sign at all
+    await program.methods
+      .stakeFor(beneficiary.publicKey, new anchor.BN(5_000))
+      .accounts({
+        funder: admin,
+        pool: poolPda,
+        userStake: userStakePda,
+        stakeMint,
+        funderStakeAta,
+        stakeVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const position = await program.account.userStake.fetch(userStakePda);
+    console.log("Beneficiary Staked:", position.amountStaked.toString());
+    if (!position.owner.equals(beneficiary.publicKey) || position.amountStaked.toNumber() !== 5_000) {
+      throw new Error("Position was not credited to the beneficiary");
+    }
+
+    // The funder has no claim on the position
+    try {
+      await program.methods
+        .unstake(new anchor.BN(5_000), false)
+        .accounts({
+          user: admin,
+          pool: poolPda,
+          userStake: userStakePda,
+          userStakeAta: funderStakeAta,
+          stakeVault,
+          stakeMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      throw new Error("Funder was able to unstake the beneficiary's position");
+    } catch (err) {
+      if (!`${err}`.includes("ConstraintSeeds")) {
+        throw err;
+      }
+      console.log("Funder unstake rejected as expected");
+    }
+
+    await program.methods
+      .unstake(new anchor.BN(5_000), false)
+      .accounts({
+        user: beneficiary.publicKey,
+        pool: poolPda,
+        userStake: userStakePda,
+        userStakeAta: beneficiaryStakeAta,
+        stakeVault,
+        stakeMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([beneficiary])
+      .rpc();
+
+    const returned = await getAccount(connection, beneficiaryStakeAta);
+    if (returned.amount !== BigInt(5_000)) {
+      throw new Error("Principal did not return to the beneficiary");
+    }
+  });
+});